@@ -0,0 +1,95 @@
+use git2::Time;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats `time` like git's default date format in the time's own
+/// offset, e.g. `Wed Oct 14 17:17:08 2026 +0200`.
+pub(crate) fn format_git_date(time: Time) -> String {
+    let date = LocalDate::new(time);
+    format!(
+        "{} {} {} {:02}:{:02}:{:02} {} {}",
+        WEEKDAYS[date.weekday as usize],
+        MONTHS[(date.month - 1) as usize],
+        date.day,
+        date.hour,
+        date.minute,
+        date.second,
+        date.year,
+        date.offset(),
+    )
+}
+
+/// Formats `time` like git's `--date=iso` format in the time's own
+/// offset, e.g. `2026-10-14 17:17:08 +0200`.
+pub(crate) fn format_iso_date(time: Time) -> String {
+    let date = LocalDate::new(time);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}",
+        date.year,
+        date.month,
+        date.day,
+        date.hour,
+        date.minute,
+        date.second,
+        date.offset(),
+    )
+}
+
+struct LocalDate {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    /// `0` is Sunday.
+    weekday: i64,
+    offset_minutes: i64,
+}
+
+impl LocalDate {
+    fn new(time: Time) -> Self {
+        let offset_minutes = i64::from(time.offset_minutes());
+        let local = time.seconds() + offset_minutes * 60;
+
+        let days = local.div_euclid(86400);
+        let secs = local.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: secs / 3600,
+            minute: secs / 60 % 60,
+            second: secs % 60,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7),
+            offset_minutes,
+        }
+    }
+
+    fn offset(&self) -> String {
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        let offset = self.offset_minutes.abs();
+        format!("{}{:02}{:02}", sign, offset / 60, offset % 60)
+    }
+}
+
+/// Converts days since the Unix epoch into a `(year, month, day)`
+/// date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -1,14 +1,27 @@
 pub mod prelude {
-    pub use super::{CommitExt, Commits, DiffExt, RepositoryExt};
+    pub use super::{
+        BranchTips, CommitExt, CommitInfo, Commits, DecoratedCommits, DiffExt, FileHistory,
+        FilteredCommits, MaxFilesCommits, ReferenceTargets, RepositoryExt, SignatureExt,
+        SignedCommits, TaggedCommit,
+    };
 }
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::Enumerate;
 use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 
 use git2::{
-    Commit, Diff, DiffDelta, DiffFormat, DiffHunk, DiffLine, DiffOptions, ErrorCode, Repository,
-    Revwalk, Sort, Tree,
+    BranchType, Branches, Buf, Commit, Delta, Diff, DiffDelta, DiffFindOptions, DiffFormat,
+    DiffHunk, DiffLine, DiffOptions, DiffStats, ErrorClass, ErrorCode, Object, ObjectType, Oid,
+    Pathspec, PathspecFlags, References, Repository, RevparseMode, Revwalk, Signature, Sort,
+    Status, StatusOptions, Time, Tree, TreeWalkMode, TreeWalkResult, Worktree,
 };
 
+use crate::date::{format_git_date, format_iso_date};
+use crate::signing::signing_key_id;
+use crate::stat::format_stat;
 use crate::GitError;
 
 pub trait WalkOutput {
@@ -51,101 +64,1996 @@ pub trait RepositoryExt {
     fn commits(&self) -> Result<Commits<'_>, GitError>;
     fn count_commits(&self) -> Result<usize, GitError>;
 
+    /// Returns the OIDs of the commits reachable from `HEAD`, walked
+    /// in `sort` order.
+    ///
+    /// Unlike [`Commit`], an [`Oid`] does not borrow the repository,
+    /// so the walk can be split up and processed in parallel, with each
+    /// thread opening its own [`Repository`] and calling
+    /// [`Repository::find_commit()`].
+    fn commit_oids(&self, sort: Sort) -> Result<Vec<Oid>, GitError>;
+
+    /// Returns the commit `HEAD` points to, without setting up a walk.
+    ///
+    /// Returns an [`ErrorCode::UnbornBranch`] error if the repository
+    /// has no commits yet.
+    fn head_commit(&self) -> Result<Commit<'_>, GitError>;
+
+    /// Returns the commit `rev` resolves to, e.g. a full or abbreviated
+    /// SHA, a branch or tag name, or any other revspec like `HEAD~2`.
+    ///
+    /// Errors from resolving `rev`, e.g. for an ambiguous abbreviation,
+    /// are returned unchanged.
+    fn resolve_commit(&self, rev: &str) -> Result<Commit<'_>, GitError>;
+
+    /// Returns the commits reachable from `HEAD` walked in `sort` order,
+    /// skipping everything up to and including `after`.
+    ///
+    /// Together with [`Commits::checkpoint()`] this allows resuming
+    /// an interrupted walk. The walk must be set up the same way as
+    /// the one the checkpoint came from, i.e. from `HEAD` with the same
+    /// `sort`, e.g. `Sort::REVERSE | Sort::TIME` for
+    /// [`commits()`](RepositoryExt::commits).
+    /// Otherwise the resumed walk yields a different set of commits.
+    /// Returns an error if `after` is not part of the walk.
+    fn commits_resume(&self, after: Oid, sort: Sort) -> Result<Commits<'_>, GitError>;
+
+    /// Returns the commits reachable from `rev` instead of `HEAD`,
+    /// e.g. a branch name, tag, or SHA, walked in `sort` order.
+    ///
+    /// Use [`Sort::TIME`] for the newest first order of `git log <rev>`.
+    /// Returns an error if `rev` cannot be resolved to a commit.
+    fn commits_from(&self, rev: &str, sort: Sort) -> Result<Commits<'_>, GitError>;
+
+    /// Returns the commits in `range`, walked in `sort` order, like
+    /// `git log <range>` with [`Sort::TIME`].
+    ///
+    /// - `A..B` yields commits reachable from `B` but not from `A`
+    /// - `A...B` yields commits reachable from either `A` or `B`,
+    ///   but not from both
+    /// - A single revision yields everything reachable from it,
+    ///   like [`commits_from()`](RepositoryExt::commits_from)
+    ///
+    /// Like git, an omitted endpoint (`A..` or `..B`) defaults to `HEAD`.
+    /// Returns an error if the range cannot be parsed, or if either
+    /// endpoint cannot be resolved to a commit.
+    fn commits_range(&self, range: &str, sort: Sort) -> Result<Commits<'_>, GitError>;
+
+    /// Returns the commits of a revwalk configured entirely by `f`.
+    ///
+    /// The revwalk starts out empty and with git2's default sorting,
+    /// so `f` must push at least one commit, e.g. with
+    /// [`Revwalk::push_head()`]. This is an escape hatch for
+    /// push/hide/sort combinations not covered by other methods.
+    fn commits_custom<F>(&self, f: F) -> Result<Commits<'_>, GitError>
+    where
+        F: FnOnce(&mut Revwalk<'_>) -> Result<(), GitError>;
+
+    /// Returns the commits reachable from any of the `include` revisions,
+    /// but not from any of the `exclude` revisions, e.g. commits
+    /// on a branch that are not on any release branch.
+    fn commits_reachable_excluding(
+        &self,
+        include: &[&str],
+        exclude: &[&str],
+    ) -> Result<Commits<'_>, GitError>;
+
+    /// Returns the commits in `from..to` newest first, i.e. those
+    /// reachable from `to` but not from `from`, e.g. the changes since
+    /// the last release with `commits_between("v1.0", "HEAD")`.
+    ///
+    /// `from` need not be an ancestor of `to`. Returns an error if
+    /// either cannot be resolved to a commit.
+    fn commits_between(&self, from: &str, to: &str) -> Result<Commits<'_>, GitError>;
+
+    /// Returns the same commits as [`commits()`](RepositoryExt::commits),
+    /// but only those that are signed.
+    ///
+    /// The signature is not verified. To verify a commit, pass the
+    /// signature and signed data from [`Repository::extract_signature()`]
+    /// to e.g. `gpg --verify` or `ssh-keygen -Y verify`.
+    fn commits_signed(&self) -> Result<SignedCommits<'_>, GitError>;
+
+    /// Returns the same commits as [`commits()`](RepositoryExt::commits),
+    /// but only those changing at most `n` files relative to their first
+    /// parent, e.g. to skip bulk imports or mechanical commits.
+    ///
+    /// This is not free, as it requires diffing every commit.
+    fn commits_max_files(&self, n: usize) -> Result<MaxFilesCommits<'_>, GitError>;
+
+    /// Returns the commits reachable from `HEAD`, newest first, stopping
+    /// at the first commit whose committer time is before `secs`
+    /// (seconds since the Unix epoch).
+    ///
+    /// Unlike [`commits()`](RepositoryExt::commits), the walk is sorted
+    /// by time with the newest commits first, which is what allows
+    /// stopping early without traversing the whole history. Commits with
+    /// skewed clocks, i.e. newer than one of their descendants that is
+    /// already past the cutoff, may therefore be omitted.
+    ///
+    /// This is the same as [`Commits::since()`] on a [`Sort::TIME`] walk.
+    fn commits_until_older_than(&self, secs: i64) -> Result<FilteredCommits<'_>, GitError>;
+
+    /// Returns the same commits as [`commits()`](RepositoryExt::commits),
+    /// along with the short names of all references pointing at each
+    /// commit, like `git log --decorate`.
+    ///
+    /// The references are resolved once upfront, not per commit.
+    fn commits_decorated(&self) -> Result<DecoratedCommits<'_>, GitError>;
+
+    /// Returns all linked worktrees of the repository.
+    ///
+    /// Worktrees with non-UTF-8 names are skipped.
+    fn linked_worktrees(&self) -> Result<Vec<Worktree>, GitError>;
+
+    /// Traces the content of `path` across the history, returning every
+    /// commit where the content changed or moved, along with the path
+    /// it had in that commit.
+    ///
+    /// A commit is only considered to change the content if none of its
+    /// parents has the same blob at the tracked path, such that commits
+    /// on other branches or merges that kept one side are not reported.
+    ///
+    /// When the tracked path goes away, the trace continues from a path
+    /// containing the exact same blob, even if git never recorded a rename
+    /// (e.g. delete and re-add in a later commit). Otherwise, a rename
+    /// relative to a parent is detected by content similarity, with
+    /// libgit2's default threshold of 50%, to follow a rename and edit
+    /// in the same commit.
+    fn trace_content<P>(&self, path: P) -> Result<Vec<(Commit<'_>, PathBuf)>, GitError>
+    where
+        P: AsRef<Path>;
+
+    /// Returns the commits that changed `path` newest first, following
+    /// renames like `git log --follow <path>`.
+    ///
+    /// Each commit is yielded with how it changed the file, and the path
+    /// the file had after the change, or before it for deletions. When a
+    /// rename is found, older commits are matched against the old path.
+    ///
+    /// A merge commit is only yielded if it changed the file relative to
+    /// each of its parents, and its change is the one relative to the
+    /// first parent.
+    fn file_history<P>(&self, path: P) -> Result<FileHistory<'_>, GitError>
+    where
+        P: AsRef<Path>;
+
+    /// Returns the commit `refname` pointed at, at the given `time`,
+    /// according to the reflog.
+    ///
+    /// `refname` can be a full name, e.g. `"refs/heads/main"`, or a short
+    /// name, e.g. `"main"`, and fails with [`ErrorCode::NotFound`] if
+    /// the ref does not exist.
+    ///
+    /// Returns `Ok(None)` if the reflog does not go back that far,
+    /// or if the ref did not exist at that time.
+    fn ref_at_time(&self, refname: &str, time: Time) -> Result<Option<Commit<'_>>, GitError>;
+
+    /// Returns the content of the blob `oid`.
+    fn blob_bytes(&self, oid: Oid) -> Result<Vec<u8>, GitError>;
+
+    /// Returns the size of the blob `oid` in bytes, read from the object
+    /// header without loading the content.
+    fn blob_size(&self, oid: Oid) -> Result<u64, GitError>;
+
+    /// Returns the aggregate diff stats between the revisions `old`
+    /// and `new`, e.g. `"v1.0"` and `"HEAD"`.
+    fn diff_stats(&self, old: &str, new: &str) -> Result<DiffStats, GitError>;
+
+    /// Diffs the tree of `old` against the tree of `new`, e.g. a release
+    /// tag against `HEAD`, or the empty tree against `new` if `old` is
+    /// `None`.
+    fn walk_commit_diff<T, F>(
+        &self,
+        old: Option<&Commit<'_>>,
+        new: &Commit<'_>,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Returns `true` if the object `oid` exists in the object database.
+    ///
+    /// Useful for partial or shallow clones, where objects referenced
+    /// by the history may be missing locally. Returns `false` if the
+    /// object database cannot be opened.
+    fn object_exists(&self, oid: Oid) -> bool;
+
+    /// Returns the best common ancestor of all `revs`, e.g. for
+    /// analyzing octopus merges.
+    fn find_merge_base_many(&self, revs: &[&str]) -> Result<Commit<'_>, GitError>;
+
+    /// Returns the best common ancestor of `a` and `b`, i.e. the point
+    /// where a branch forked, like `git merge-base a b`.
+    ///
+    /// Returns an [`ErrorCode::NotFound`] error if they have no common
+    /// ancestor.
+    fn find_merge_base(&self, a: &str, b: &str) -> Result<Commit<'_>, GitError>;
+
+    /// Returns all best common ancestors of `a` and `b`, of which there
+    /// can be more than one after criss-cross merges, like
+    /// `git merge-base --all a b`.
+    fn find_merge_bases(&self, a: &str, b: &str) -> Result<Vec<Commit<'_>>, GitError>;
+
+    /// Returns all references (branches, tags, notes, stash, etc.),
+    /// yielding the full reference name and the OID it resolves to.
+    ///
+    /// Symbolic references are resolved. Non-UTF-8 names are
+    /// converted lossily.
+    fn reference_targets(&self) -> Result<ReferenceTargets<'_>, GitError>;
+
+    /// Returns the branches, optionally only local or remote ones,
+    /// yielding the branch name and its tip commit, e.g.
+    /// `("main", ...)` or `("origin/main", ...)`.
+    ///
+    /// Non-UTF-8 names are converted lossily.
+    fn branch_tips(&self, filter: Option<BranchType>) -> Result<BranchTips<'_>, GitError>;
+
+    /// Returns the names of all tags, optionally only those matching
+    /// the fnmatch `pattern`, e.g. `"v1.*"`.
+    ///
+    /// Unlike [`Repository::tag_names()`] this returns owned names.
+    /// Tags with non-UTF-8 names are skipped.
+    fn tag_names_matching(&self, pattern: Option<&str>) -> Result<Vec<String>, GitError>;
+
+    /// Returns all tags sorted by name, with the commit each tag points
+    /// to, resolving annotated tags.
+    ///
+    /// Tags that do not point to a commit, e.g. a tag of a tree, are
+    /// skipped. Non-UTF-8 names and messages are converted lossily.
+    fn tag_commits(&self) -> Result<Vec<TaggedCommit<'_>>, GitError>;
+
+    /// Returns how many commits `local` is `(ahead, behind)` of `upstream`,
+    /// e.g. `"feature"` and `"origin/main"`.
+    fn ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize), GitError>;
+
+    /// Returns `true` if the working tree and index have no changes
+    /// relative to `HEAD`, including untracked files but excluding
+    /// ignored files.
+    ///
+    /// Returns an error for bare repositories.
+    fn is_clean(&self) -> Result<bool, GitError>;
+
+    /// Diffs the tree of `HEAD` against the working tree, including
+    /// staged changes, like `git diff HEAD` plus untracked files.
+    ///
+    /// Untracked files are reported as [`Delta::Untracked`] with their
+    /// content as added lines, and ignored files are excluded. If the
+    /// repository has no commits yet, everything is diffed against the
+    /// empty tree. Returns an error for bare repositories.
+    fn walk_status_diff<T, F>(&self, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
     fn walk_commits<T, F>(&self, mut f: F) -> Result<(), GitError>
     where
-        F: FnMut(Commit<'_>) -> T,
+        F: FnMut(Commit<'_>) -> T,
+        T: WalkOutput,
+    {
+        for commit in self.commits()? {
+            if f(commit?).finished()? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Walks the changes of [`walk_status_diff()`](RepositoryExt::walk_status_diff).
+    fn walk_status_changes<T, F>(&self, format: DiffFormat, f: F) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_status_diff(|diff| diff.walk_changes(format, f))
+    }
+
+    /// Walks the changes of [`walk_commit_diff()`](RepositoryExt::walk_commit_diff).
+    fn walk_commit_changes<T, F>(
+        &self,
+        old: Option<&Commit<'_>>,
+        new: &Commit<'_>,
+        format: DiffFormat,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_commit_diff(old, new, |diff| diff.walk_changes(format, f))
+    }
+}
+
+impl RepositoryExt for Repository {
+    fn commits(&self) -> Result<Commits<'_>, GitError> {
+        Commits::new(self)
+    }
+
+    fn commits_resume(&self, after: Oid, sort: Sort) -> Result<Commits<'_>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(sort)?;
+        revwalk.push_head()?;
+
+        let mut commits =
+            Commits::from_revwalk(self, revwalk, Some(sort), Some(vec!["HEAD".to_owned()]));
+        loop {
+            let Some(oid) = commits.revwalk.next() else {
+                return Err(GitError::new(
+                    ErrorCode::NotFound,
+                    ErrorClass::Object,
+                    format!("commit {after} is not part of the walk"),
+                ));
+            };
+            if oid? == after {
+                break;
+            }
+        }
+        commits.last = Some(after);
+        Ok(commits)
+    }
+
+    fn commits_from(&self, rev: &str, sort: Sort) -> Result<Commits<'_>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(sort)?;
+        revwalk.push(self.resolve_commit(rev)?.id())?;
+
+        Ok(Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(sort),
+            Some(vec![rev.to_owned()]),
+        ))
+    }
+
+    fn head_commit(&self) -> Result<Commit<'_>, GitError> {
+        match self.head() {
+            Ok(head) => head.peel_to_commit(),
+            Err(err) if err.code() == ErrorCode::UnbornBranch => Err(GitError::new(
+                ErrorCode::UnbornBranch,
+                ErrorClass::Reference,
+                format!("the repository has no commits yet ({})", err.message()),
+            )),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<Commit<'_>, GitError> {
+        self.revparse_single(rev)?.peel_to_commit()
+    }
+
+    fn commits_range(&self, range: &str, sort: Sort) -> Result<Commits<'_>, GitError> {
+        let spec = self.revparse(range)?;
+        let endpoint = |object: Option<&Object<'_>>| match object {
+            Some(object) => Ok(object.peel_to_commit()?.id()),
+            None => Err(GitError::new(
+                ErrorCode::NotFound,
+                ErrorClass::Object,
+                format!("missing endpoint in range '{range}'"),
+            )),
+        };
+
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(sort)?;
+        if spec.mode().contains(RevparseMode::SINGLE) {
+            revwalk.push(endpoint(spec.from())?)?;
+        } else {
+            let from = endpoint(spec.from())?;
+            let to = endpoint(spec.to())?;
+            if spec.mode().contains(RevparseMode::MERGE_BASE) {
+                revwalk.push(from)?;
+                revwalk.push(to)?;
+                match self.merge_bases(from, to) {
+                    Ok(bases) => {
+                        for &base in bases.iter() {
+                            revwalk.hide(base)?;
+                        }
+                    }
+                    // Unrelated histories
+                    Err(err) if err.code() == ErrorCode::NotFound => {}
+                    Err(err) => return Err(err),
+                }
+            } else {
+                revwalk.hide(from)?;
+                revwalk.push(to)?;
+            }
+        }
+
+        Ok(Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(sort),
+            Some(vec![range.to_owned()]),
+        ))
+    }
+
+    fn commits_custom<F>(&self, f: F) -> Result<Commits<'_>, GitError>
+    where
+        F: FnOnce(&mut Revwalk<'_>) -> Result<(), GitError>,
+    {
+        let mut revwalk = self.revwalk()?;
+        f(&mut revwalk)?;
+        Ok(Commits::from_revwalk(self, revwalk, None, None))
+    }
+
+    fn commits_reachable_excluding(
+        &self,
+        include: &[&str],
+        exclude: &[&str],
+    ) -> Result<Commits<'_>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(DEFAULT_SORT)?;
+        for rev in include {
+            revwalk.push(self.resolve_commit(rev)?.id())?;
+        }
+        for rev in exclude {
+            revwalk.hide(self.resolve_commit(rev)?.id())?;
+        }
+
+        let pushed = include
+            .iter()
+            .map(|&rev| rev.to_owned())
+            .chain(exclude.iter().map(|rev| format!("^{rev}")))
+            .collect();
+        Ok(Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(DEFAULT_SORT),
+            Some(pushed),
+        ))
+    }
+
+    fn commits_between(&self, from: &str, to: &str) -> Result<Commits<'_>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push(self.resolve_commit(to)?.id())?;
+        revwalk.hide(self.resolve_commit(from)?.id())?;
+
+        Ok(Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(Sort::TIME),
+            Some(vec![to.to_owned(), format!("^{from}")]),
+        ))
+    }
+
+    fn commits_signed(&self) -> Result<SignedCommits<'_>, GitError> {
+        Ok(SignedCommits {
+            commits: Commits::new(self)?,
+        })
+    }
+
+    fn commits_max_files(&self, n: usize) -> Result<MaxFilesCommits<'_>, GitError> {
+        Ok(MaxFilesCommits {
+            commits: Commits::new(self)?,
+            max_files: n,
+        })
+    }
+
+    fn commits_until_older_than(&self, secs: i64) -> Result<FilteredCommits<'_>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push_head()?;
+
+        let commits = Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(Sort::TIME),
+            Some(vec!["HEAD".to_owned()]),
+        );
+        Ok(commits.since(secs))
+    }
+
+    fn commits_decorated(&self) -> Result<DecoratedCommits<'_>, GitError> {
+        let mut decorations = HashMap::<Oid, Vec<String>>::new();
+        if let Ok(head) = self.head() {
+            if let Ok(commit) = head.peel_to_commit() {
+                decorations
+                    .entry(commit.id())
+                    .or_default()
+                    .push("HEAD".to_owned());
+            }
+        }
+        for reference in self.references()? {
+            let reference = reference?;
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            let name = String::from_utf8_lossy(reference.shorthand_bytes()).into_owned();
+            decorations.entry(commit.id()).or_default().push(name);
+        }
+
+        Ok(DecoratedCommits {
+            commits: Commits::new(self)?,
+            decorations,
+        })
+    }
+
+    fn count_commits(&self) -> Result<usize, GitError> {
+        Ok(revwalk(self)?.count())
+    }
+
+    fn commit_oids(&self, sort: Sort) -> Result<Vec<Oid>, GitError> {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(sort)?;
+        revwalk.push_head()?;
+        revwalk.collect()
+    }
+
+    fn linked_worktrees(&self) -> Result<Vec<Worktree>, GitError> {
+        self.worktrees()?
+            .iter()
+            .flatten()
+            .map(|name| self.find_worktree(name))
+            .collect()
+    }
+
+    fn file_history<P>(&self, path: P) -> Result<FileHistory<'_>, GitError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push_head()?;
+
+        let commits = Commits::from_revwalk(
+            self,
+            revwalk,
+            Some(Sort::TIME),
+            Some(vec!["HEAD".to_owned()]),
+        );
+        Ok(FileHistory {
+            commits,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    fn trace_content<P>(&self, path: P) -> Result<Vec<(Commit<'_>, PathBuf)>, GitError>
+    where
+        P: AsRef<Path>,
+    {
+        // Parents must be visited before their children, such that each
+        // commit can continue from the content traced in its parents
+        let mut revwalk = self.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | DEFAULT_SORT)?;
+        revwalk.push_head()?;
+
+        let path = path.as_ref();
+        // The path and blob last traced as of each visited commit
+        let mut traced: HashMap<Oid, Option<(PathBuf, Oid)>> = HashMap::new();
+        let mut trace = Vec::new();
+        for oid in revwalk {
+            let commit = self.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parents = commit.parents().collect::<Vec<_>>();
+            let last = parents
+                .iter()
+                .find_map(|parent| traced.get(&parent.id()).cloned().flatten());
+
+            let current = match &last {
+                Some((path, _)) => path.as_path(),
+                None => path,
+            };
+            let found = match find_blob_id(&tree, current)? {
+                Some(id) => {
+                    // Only a change if no parent has the same content at that path
+                    let mut changed = true;
+                    for parent in &parents {
+                        if find_blob_id(&parent.tree()?, current)? == Some(id) {
+                            changed = false;
+                            break;
+                        }
+                    }
+                    Some((current.to_path_buf(), id, changed))
+                }
+                None => match &last {
+                    Some((_, blob)) => match find_blob_path(&tree, *blob) {
+                        Some(found) => Some((found, *blob, true)),
+                        None => find_similar_path(self, &parents, &tree, current)?
+                            .map(|(found, id)| (found, id, true)),
+                    },
+                    None => None,
+                },
+            };
+
+            match found {
+                Some((found, id, changed)) => {
+                    if changed {
+                        trace.push((commit.clone(), found.clone()));
+                    }
+                    traced.insert(commit.id(), Some((found, id)));
+                }
+                None => {
+                    traced.insert(commit.id(), last);
+                }
+            }
+        }
+        Ok(trace)
+    }
+
+    fn ref_at_time(&self, refname: &str, time: Time) -> Result<Option<Commit<'_>>, GitError> {
+        let reference = self.resolve_reference_from_short_name(refname)?;
+        let Some(name) = reference.name() else {
+            return Err(GitError::new(
+                ErrorCode::Invalid,
+                ErrorClass::Reference,
+                format!("reference name for {refname:?} is not valid UTF-8"),
+            ));
+        };
+        let reflog = self.reflog(name)?;
+        let entry = reflog
+            .iter()
+            .find(|entry| entry.committer().when().seconds() <= time.seconds());
+        match entry {
+            Some(entry) if !entry.id_new().is_zero() => self.find_commit(entry.id_new()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn blob_bytes(&self, oid: Oid) -> Result<Vec<u8>, GitError> {
+        Ok(self.find_blob(oid)?.content().to_vec())
+    }
+
+    fn blob_size(&self, oid: Oid) -> Result<u64, GitError> {
+        let (size, kind) = self.odb()?.read_header(oid)?;
+        if kind != ObjectType::Blob {
+            return Err(GitError::new(
+                ErrorCode::Invalid,
+                ErrorClass::Object,
+                format!("object {oid} is not a blob"),
+            ));
+        }
+        Ok(size as u64)
+    }
+
+    fn diff_stats(&self, old: &str, new: &str) -> Result<DiffStats, GitError> {
+        let old_tree = self.revparse_single(old)?.peel_to_tree()?;
+        let new_tree = self.revparse_single(new)?.peel_to_tree()?;
+        diff_trees(self, Some(&old_tree), Some(&new_tree))?.stats()
+    }
+
+    fn walk_commit_diff<T, F>(
+        &self,
+        old: Option<&Commit<'_>>,
+        new: &Commit<'_>,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
+    {
+        let old_tree = old.map(Commit::tree).transpose()?;
+        walk_diff(self, old_tree.as_ref(), Some(&new.tree()?), f)
+    }
+
+    fn object_exists(&self, oid: Oid) -> bool {
+        self.odb().is_ok_and(|odb| odb.exists(oid))
+    }
+
+    fn find_merge_base_many(&self, revs: &[&str]) -> Result<Commit<'_>, GitError> {
+        let oids = revs
+            .iter()
+            .map(|rev| Ok(self.resolve_commit(rev)?.id()))
+            .collect::<Result<Vec<_>, GitError>>()?;
+        self.find_commit(self.merge_base_many(&oids)?)
+    }
+
+    fn find_merge_base(&self, a: &str, b: &str) -> Result<Commit<'_>, GitError> {
+        let a = self.resolve_commit(a)?.id();
+        let b = self.resolve_commit(b)?.id();
+        self.find_commit(self.merge_base(a, b)?)
+    }
+
+    fn find_merge_bases(&self, a: &str, b: &str) -> Result<Vec<Commit<'_>>, GitError> {
+        let a = self.resolve_commit(a)?.id();
+        let b = self.resolve_commit(b)?.id();
+        self.merge_bases(a, b)?
+            .iter()
+            .map(|&oid| self.find_commit(oid))
+            .collect()
+    }
+
+    fn reference_targets(&self) -> Result<ReferenceTargets<'_>, GitError> {
+        Ok(ReferenceTargets {
+            references: self.references()?,
+        })
+    }
+
+    fn branch_tips(&self, filter: Option<BranchType>) -> Result<BranchTips<'_>, GitError> {
+        Ok(BranchTips {
+            branches: self.branches(filter)?,
+        })
+    }
+
+    fn tag_names_matching(&self, pattern: Option<&str>) -> Result<Vec<String>, GitError> {
+        Ok(self
+            .tag_names(pattern)?
+            .iter()
+            .flatten()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    fn tag_commits(&self) -> Result<Vec<TaggedCommit<'_>>, GitError> {
+        let mut tags = Vec::new();
+        for reference in self.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            // Peels annotated tags, possibly of other tags
+            let Ok(commit) = reference.peel(ObjectType::Any)?.into_commit() else {
+                continue;
+            };
+            let message = match reference.peel_to_tag() {
+                Ok(tag) => tag
+                    .message_bytes()
+                    .map(|message| String::from_utf8_lossy(message).into_owned()),
+                Err(_) => None,
+            };
+            let name = String::from_utf8_lossy(reference.shorthand_bytes()).into_owned();
+            tags.push(TaggedCommit {
+                name,
+                commit,
+                message,
+            });
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    fn ahead_behind(&self, local: &str, upstream: &str) -> Result<(usize, usize), GitError> {
+        let local = self.resolve_commit(local)?.id();
+        let upstream = self.resolve_commit(upstream)?.id();
+        self.graph_ahead_behind(local, upstream)
+    }
+
+    fn is_clean(&self) -> Result<bool, GitError> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+
+        let statuses = self.statuses(Some(&mut opts))?;
+        Ok(statuses
+            .iter()
+            .all(|entry| entry.status() == Status::CURRENT))
+    }
+
+    fn walk_status_diff<T, F>(&self, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
+    {
+        let head_tree = match self.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(err) if err.code() == ErrorCode::UnbornBranch => None,
+            Err(err) => return Err(err),
+        };
+
+        let mut opts = diff_options();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+
+        let mut diff = self.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?;
+        diff.find_similar(None)?;
+        f(diff).finished()?;
+        Ok(())
+    }
+}
+
+const DEFAULT_SORT: Sort = Sort::REVERSE.union(Sort::TIME);
+
+pub struct Commits<'a> {
+    repo: &'a Repository,
+    revwalk: Revwalk<'a>,
+    /// `None` if the revwalk was configured by the caller.
+    sort: Option<Sort>,
+    /// `None` if the revwalk was configured by the caller.
+    pushed: Option<Vec<String>>,
+    last: Option<Oid>,
+}
+
+impl<'a> Commits<'a> {
+    fn new(repo: &'a Repository) -> Result<Self, GitError> {
+        let revwalk = revwalk(repo)?;
+        Ok(Self::from_revwalk(
+            repo,
+            revwalk,
+            Some(DEFAULT_SORT),
+            Some(vec!["HEAD".to_owned()]),
+        ))
+    }
+
+    fn from_revwalk(
+        repo: &'a Repository,
+        revwalk: Revwalk<'a>,
+        sort: Option<Sort>,
+        pushed: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            repo,
+            revwalk,
+            sort,
+            pushed,
+            last: None,
+        }
+    }
+
+    /// Returns the OID of the last yielded commit, which can be passed
+    /// to [`RepositoryExt::commits_resume()`] to continue the walk later.
+    pub fn checkpoint(&self) -> Option<Oid> {
+        self.last
+    }
+
+    /// Pairs each commit with its zero-based index in the walk,
+    /// e.g. for numbered changelog entries.
+    ///
+    /// This is a convenience for [`Iterator::enumerate()`].
+    pub fn with_index(self) -> Enumerate<Self> {
+        self.enumerate()
+    }
+
+    /// Walks the remaining commits and returns them as owned snapshots
+    /// in reverse order, e.g. to replay a newest first walk oldest first.
+    ///
+    /// Unlike [`Sort::REVERSE`], this does not require walking again.
+    pub fn collect_reversed(self) -> Result<Vec<CommitInfo>, GitError> {
+        let mut infos = self
+            .map(|commit| commit.map(|commit| commit.to_info()))
+            .collect::<Result<Vec<_>, _>>()?;
+        infos.reverse();
+        Ok(infos)
+    }
+
+    /// Only yields commits whose author name or email contains `pattern`,
+    /// ignoring case, like `git log --author=<pattern>`.
+    ///
+    /// This composes with any walk, e.g.
+    /// `repo.commits_range("v1..v2", Sort::TIME)?.filter_author(pattern)`
+    /// is like `git log --author=<pattern> v1..v2`, and can be narrowed
+    /// further with [`since()`](FilteredCommits::since) and
+    /// [`until()`](FilteredCommits::until).
+    pub fn filter_author(self, pattern: &str) -> FilteredCommits<'a> {
+        FilteredCommits::new(self).filter_author(pattern)
+    }
+
+    /// Only yields commits committed at or after `ts` seconds since
+    /// the epoch, like `git log --since`.
+    ///
+    /// If the walk is sorted newest first by [`Sort::TIME`], it stops at
+    /// the first commit older than `ts`, instead of walking the rest of
+    /// the history.
+    pub fn since(self, ts: i64) -> FilteredCommits<'a> {
+        FilteredCommits::new(self).since(ts)
+    }
+
+    /// Only yields commits committed at or before `ts` seconds since
+    /// the epoch, like `git log --until`.
+    ///
+    /// If the walk is sorted oldest first, e.g. by the default
+    /// `Sort::REVERSE | Sort::TIME`, it stops at the first commit
+    /// newer than `ts`, instead of walking the rest of the history.
+    pub fn until(self, ts: i64) -> FilteredCommits<'a> {
+        FilteredCommits::new(self).until(ts)
+    }
+}
+
+impl fmt::Debug for Commits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Commits")
+            .field("sort", &self.sort)
+            .field("pushed", &self.pushed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Iterator for Commits<'a> {
+    type Item = Result<git2::Commit<'a>, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let oid = match self.revwalk.next()? {
+            Ok(oid) => oid,
+            Err(err) => return Some(Err(err)),
+        };
+        let commit = match self.repo.find_commit(oid) {
+            Ok(commit) => commit,
+            Err(err) => return Some(Err(err)),
+        };
+        self.last = Some(oid);
+        Some(Ok(commit))
+    }
+}
+
+/// Commits filtered by author and committer time, see
+/// [`Commits::filter_author()`], [`Commits::since()`], and
+/// [`Commits::until()`]. The filters can be chained in any order.
+pub struct FilteredCommits<'a> {
+    commits: Commits<'a>,
+    /// Lowercase.
+    author: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+    done: bool,
+}
+
+impl<'a> FilteredCommits<'a> {
+    fn new(commits: Commits<'a>) -> Self {
+        Self {
+            commits,
+            author: None,
+            since: None,
+            until: None,
+            done: false,
+        }
+    }
+
+    /// See [`Commits::filter_author()`].
+    pub fn filter_author(mut self, pattern: &str) -> Self {
+        self.author = Some(pattern.to_lowercase());
+        self
+    }
+
+    /// See [`Commits::since()`].
+    pub fn since(mut self, ts: i64) -> Self {
+        self.since = Some(ts);
+        self
+    }
+
+    /// See [`Commits::until()`].
+    pub fn until(mut self, ts: i64) -> Self {
+        self.until = Some(ts);
+        self
+    }
+
+    fn matches_author(&self, commit: &git2::Commit<'_>) -> bool {
+        let Some(pattern) = &self.author else {
+            return true;
+        };
+        let contains = |bytes: &[u8]| {
+            String::from_utf8_lossy(bytes)
+                .to_lowercase()
+                .contains(pattern)
+        };
+        let author = commit.author();
+        contains(author.name_bytes()) || contains(author.email_bytes())
+    }
+}
+
+impl<'a> Iterator for FilteredCommits<'a> {
+    type Item = Result<git2::Commit<'a>, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let sort = self.commits.sort;
+        let newest_first = sort == Some(Sort::TIME);
+        let oldest_first = sort == Some(DEFAULT_SORT);
+        loop {
+            let commit = match self.commits.next()? {
+                Ok(commit) => commit,
+                Err(err) => return Some(Err(err)),
+            };
+            let time = commit.committer().when().seconds();
+            if self.since.is_some_and(|since| time < since) {
+                if newest_first {
+                    self.done = true;
+                    return None;
+                }
+                continue;
+            }
+            if self.until.is_some_and(|until| time > until) {
+                if oldest_first {
+                    self.done = true;
+                    return None;
+                }
+                continue;
+            }
+            if self.matches_author(&commit) {
+                return Some(Ok(commit));
+            }
+        }
+    }
+}
+
+pub struct DecoratedCommits<'a> {
+    commits: Commits<'a>,
+    decorations: HashMap<Oid, Vec<String>>,
+}
+
+impl<'a> Iterator for DecoratedCommits<'a> {
+    type Item = Result<(git2::Commit<'a>, Vec<String>), GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let commit = match self.commits.next()? {
+            Ok(commit) => commit,
+            Err(err) => return Some(Err(err)),
+        };
+        let decorations = self.decorations.remove(&commit.id()).unwrap_or_default();
+        Some(Ok((commit, decorations)))
+    }
+}
+
+pub struct MaxFilesCommits<'a> {
+    commits: Commits<'a>,
+    max_files: usize,
+}
+
+impl<'a> Iterator for MaxFilesCommits<'a> {
+    type Item = Result<git2::Commit<'a>, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let commit = match self.commits.next()? {
+                Ok(commit) => commit,
+                Err(err) => return Some(Err(err)),
+            };
+            let mut files = 0;
+            let res = commit.walk_diff_first_parent(self.commits.repo, |diff| {
+                files = diff.deltas().len();
+            });
+            match res {
+                Ok(()) if files <= self.max_files => return Some(Ok(commit)),
+                Ok(()) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+pub struct FileHistory<'a> {
+    commits: Commits<'a>,
+    /// The path of the file as of the last yielded commit.
+    path: PathBuf,
+}
+
+impl FileHistory<'_> {
+    /// Returns the change to `self.path` in `diff`, along with the path
+    /// to report and the path to follow in older commits.
+    fn find_change(&self, diff: &Diff<'_>) -> Option<(Delta, PathBuf, PathBuf)> {
+        diff.deltas().find_map(|delta| {
+            let old_path = delta.old_file().path();
+            let new_path = delta.new_file().path();
+            match delta.status() {
+                Delta::Deleted if old_path == Some(&self.path) => {
+                    Some((Delta::Deleted, self.path.clone(), self.path.clone()))
+                }
+                Delta::Deleted => None,
+                status if new_path == Some(&self.path) => {
+                    let old_path = old_path.unwrap_or(&self.path);
+                    Some((status, self.path.clone(), old_path.to_path_buf()))
+                }
+                _ => None,
+            }
+        })
+    }
+}
+
+impl<'a> Iterator for FileHistory<'a> {
+    type Item = Result<(git2::Commit<'a>, Delta, PathBuf), GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let commit = match self.commits.next()? {
+                Ok(commit) => commit,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let mut changes = Vec::with_capacity(commit.parent_count().max(1));
+            let res = commit.walk_diffs(self.commits.repo, |diff| {
+                changes.push(self.find_change(&diff));
+            });
+            if let Err(err) = res {
+                return Some(Err(err));
+            }
+
+            // Skip the commit if the file is unchanged relative to any parent
+            let Some(changes) = changes.into_iter().collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+            let Some((status, path, old_path)) = changes.into_iter().next() else {
+                continue;
+            };
+            self.path = old_path;
+            return Some(Ok((commit, status, path)));
+        }
+    }
+}
+
+pub struct SignedCommits<'a> {
+    commits: Commits<'a>,
+}
+
+impl<'a> Iterator for SignedCommits<'a> {
+    type Item = Result<git2::Commit<'a>, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let commit = match self.commits.next()? {
+                Ok(commit) => commit,
+                Err(err) => return Some(Err(err)),
+            };
+            match commit.is_signed(self.commits.repo) {
+                Ok(true) => return Some(Ok(commit)),
+                Ok(false) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+fn revwalk(repo: &Repository) -> Result<Revwalk<'_>, GitError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(DEFAULT_SORT)?;
+    revwalk.push_head()?;
+    Ok(revwalk)
+}
+
+pub struct ReferenceTargets<'a> {
+    references: References<'a>,
+}
+
+impl Iterator for ReferenceTargets<'_> {
+    type Item = Result<(String, Oid), GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reference = match self.references.next()? {
+                Ok(reference) => reference,
+                Err(err) => return Some(Err(err)),
+            };
+            let resolved = match reference.resolve() {
+                Ok(resolved) => resolved,
+                Err(err) => return Some(Err(err)),
+            };
+            if let Some(oid) = resolved.target() {
+                let name = String::from_utf8_lossy(reference.name_bytes()).into_owned();
+                return Some(Ok((name, oid)));
+            }
+        }
+    }
+}
+
+/// A tag and the commit it points to, see [`RepositoryExt::tag_commits()`].
+#[derive(Clone, Debug)]
+pub struct TaggedCommit<'a> {
+    /// The tag name without `refs/tags/`, e.g. `"v1.0"`.
+    pub name: String,
+    pub commit: Commit<'a>,
+    /// The message of an annotated tag, or `None` for lightweight tags.
+    pub message: Option<String>,
+}
+
+pub struct BranchTips<'a> {
+    branches: Branches<'a>,
+}
+
+impl<'a> Iterator for BranchTips<'a> {
+    type Item = Result<(String, Commit<'a>), GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (branch, _) = match self.branches.next()? {
+            Ok(branch) => branch,
+            Err(err) => return Some(Err(err)),
+        };
+        let name = match branch.name_bytes() {
+            Ok(name) => String::from_utf8_lossy(name).into_owned(),
+            Err(err) => return Some(Err(err)),
+        };
+        match branch.get().peel_to_commit() {
+            Ok(commit) => Some(Ok((name, commit))),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+fn find_blob_id(tree: &Tree<'_>, path: &Path) -> Result<Option<Oid>, GitError> {
+    match tree.get_path(path) {
+        Ok(entry) if entry.kind() == Some(ObjectType::Blob) => Ok(Some(entry.id())),
+        Ok(_) => Ok(None),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn find_blob_path(tree: &Tree<'_>, id: Oid) -> Option<PathBuf> {
+    let mut found = None;
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) || entry.id() != id {
+            return TreeWalkResult::Ok;
+        }
+        match entry.name() {
+            Some(name) => {
+                found = Some(Path::new(root).join(name));
+                TreeWalkResult::Abort
+            }
+            None => TreeWalkResult::Ok,
+        }
+    })
+    .ok()?;
+    found
+}
+
+/// Returns the path and blob that `path` was renamed to in `tree`
+/// relative to any of `parents`, detected by content similarity.
+fn find_similar_path(
+    repo: &Repository,
+    parents: &[Commit<'_>],
+    tree: &Tree<'_>,
+    path: &Path,
+) -> Result<Option<(PathBuf, Oid)>, GitError> {
+    for parent in parents {
+        let old_tree = parent.tree()?;
+        if find_blob_id(&old_tree, path)?.is_none() {
+            continue;
+        }
+        let diff = diff_trees(repo, Some(&old_tree), Some(tree))?;
+        let renamed = diff.deltas().find_map(|delta| {
+            if delta.status() != Delta::Renamed || delta.old_file().path() != Some(path) {
+                return None;
+            }
+            let new_path = delta.new_file().path()?;
+            Some((new_path.to_path_buf(), delta.new_file().id()))
+        });
+        if renamed.is_some() {
+            return Ok(renamed);
+        }
+    }
+    Ok(None)
+}
+
+pub trait CommitExt {
+    /// Diffs the commit against each of its parents in order, or against
+    /// the empty tree if it has no parents.
+    fn walk_diffs<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
+    where
+        F: FnMut(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Same as [`walk_diffs()`](CommitExt::walk_diffs), but with custom
+    /// diff options, e.g. [`DiffOptions::pathspec()`] to restrict the
+    /// diffs to a subtree, or [`DiffOptions::ignore_whitespace()`].
+    ///
+    /// Renames are still detected after the diff is created, with the
+    /// default options. Use
+    /// [`walk_diffs_with_find_options()`](CommitExt::walk_diffs_with_find_options)
+    /// to customize both.
+    fn walk_diffs_with<T, F>(
+        &self,
+        repo: &Repository,
+        opts: &mut DiffOptions,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Same as [`walk_diffs_with()`](CommitExt::walk_diffs_with), but
+    /// detects renames and copies with `find_opts` instead of the defaults,
+    /// e.g. to combine [`DiffOptions::max_size()`] with
+    /// [`DiffFindOptions::rename_limit()`].
+    ///
+    /// By default only renames are detected, with libgit2's default
+    /// similarity threshold of 50%. With custom options, e.g. a lower
+    /// [`DiffFindOptions::rename_threshold()`] or
+    /// [`DiffFindOptions::copies_from_unmodified()`], only `find_opts`
+    /// is used.
+    fn walk_diffs_with_find_options<T, F>(
+        &self,
+        repo: &Repository,
+        opts: &mut DiffOptions,
+        find_opts: &mut DiffFindOptions,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Diffs the commit against its first parent only, or against
+    /// the empty tree if it has no parents.
+    ///
+    /// For merge commits this is the mainline view, i.e. everything
+    /// the merge brought in from the other branches.
+    fn walk_diff_first_parent<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Diffs the commit's tree against the empty tree regardless of
+    /// its parents, such that every file is reported as added.
+    fn walk_diff_from_empty<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Diffs the commit's tree against the tree of `other`, which need
+    /// not be related to the commit, i.e. what changed going from `self`
+    /// to `other`.
+    fn walk_diff_to<T, F>(
+        &self,
+        repo: &Repository,
+        other: &Commit<'_>,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Diffs the commit against its parent at `parent_index`, e.g. `1`
+    /// for the branch that was merged in by a merge commit.
+    ///
+    /// Returns an error if the commit has no such parent.
+    fn walk_diff_against<T, F>(
+        &self,
+        repo: &Repository,
+        parent_index: usize,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput;
+
+    /// Returns `true` if the commit has more than one parent.
+    fn is_merge(&self) -> bool;
+
+    /// Returns the OIDs of all blobs reachable from the commit's tree,
+    /// in tree order and without duplicates.
+    fn blob_ids(&self) -> Result<Vec<Oid>, GitError>;
+
+    /// Returns the full hex id of each parent, e.g. for rendering
+    /// `Merge: <sha> <sha>` lines.
+    fn parent_shas(&self) -> Vec<String>;
+
+    /// Returns the abbreviated hex id of each parent, using the
+    /// shortest length that is unique within the repository.
+    fn parent_short_shas(&self) -> Result<Vec<String>, GitError>;
+
+    /// Returns the first `len` characters of the commit's hex id,
+    /// or the full id if `len` is longer.
+    fn short_sha(&self, len: usize) -> String;
+
+    /// Returns the abbreviated hex id of the commit, using the
+    /// shortest length that is unique within the repository, like
+    /// `git log --abbrev-commit`.
+    fn abbrev_sha(&self) -> Result<String, GitError>;
+
+    /// Returns the raw `(signature, payload)` of the commit, where the
+    /// payload is the commit object without the signature, or `Ok(None)`
+    /// if the commit is unsigned.
+    ///
+    /// The signature is not verified.
+    fn signature_raw(&self, repo: &Repository) -> Result<Option<(Buf, Buf)>, GitError>;
+
+    /// Returns `true` if the commit has a GPG or SSH signature.
+    ///
+    /// The signature is not verified.
+    fn is_signed(&self, repo: &Repository) -> Result<bool, GitError>;
+
+    /// Returns the key that signed the commit, without verifying
+    /// the signature.
+    ///
+    /// For GPG signatures this is the issuer key ID as uppercase hex,
+    /// e.g. `"0123456789ABCDEF"`. For SSH signatures this is the public
+    /// key fingerprint, e.g. `"SHA256:..."` like `ssh-keygen -l` prints.
+    ///
+    /// Returns `Ok(None)` if the commit is unsigned, or if the key
+    /// cannot be determined from the signature.
+    fn signing_key_id(&self, repo: &Repository) -> Result<Option<String>, GitError>;
+
+    /// Returns the paragraphs of the message body (everything after the
+    /// summary), split on blank lines and with trailing whitespace trimmed.
+    ///
+    /// Returns an empty `Vec` if there is no body, or if the message
+    /// is not valid UTF-8.
+    fn body_paragraphs(&self) -> Vec<&str>;
+
+    /// Returns the `(key, value)` pairs of the trailers at the end of the
+    /// message, e.g. `("Signed-off-by", "Jane Doe <jane@example.com>")`,
+    /// like `git interpret-trailers --parse`.
+    fn trailers(&self) -> Result<Vec<(String, String)>, GitError>;
+
+    /// Returns the `(name, email)` of each `Co-authored-by` trailer,
+    /// in the order they appear.
+    ///
+    /// Trailers that are not of the form `Name <email>` are skipped.
+    fn co_authors(&self) -> Result<Vec<(String, String)>, GitError> {
+        let co_authors = self
+            .trailers()?
+            .into_iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("Co-authored-by"))
+            .filter_map(|(_, value)| {
+                let (name, email) = value.strip_suffix('>')?.rsplit_once('<')?;
+                let (name, email) = (name.trim(), email.trim());
+                if name.is_empty() || email.is_empty() || email.contains(['<', '>']) {
+                    return None;
+                }
+                Some((name.to_owned(), email.to_owned()))
+            })
+            .collect();
+        Ok(co_authors)
+    }
+
+    /// Formats the commit like `git log --format=fuller`.
+    fn format_fuller(&self) -> Result<String, GitError>;
+
+    /// Returns `true` if the commit was likely rebased, amended,
+    /// or otherwise rewritten after it was authored.
+    ///
+    /// This is the case if the author and committer identities differ,
+    /// or if the commit was committed more than `secs` seconds after
+    /// it was authored.
+    fn likely_rewritten_within(&self, secs: i64) -> bool;
+
+    /// Formats the diffstat of the commit against its first parent,
+    /// like `git show --stat=<width> --format=`, including the summary
+    /// line.
+    fn format_stat(&self, repo: &Repository, width: usize) -> Result<String, GitError>;
+
+    /// Returns an owned snapshot of the commit's metadata, which does
+    /// not borrow the repository.
+    fn to_info(&self) -> CommitInfo;
+
+    /// Returns the number of files changed, insertions, and deletions
+    /// of the commit against its first parent, like the summary line
+    /// of `git show --stat`.
+    fn stats(&self, repo: &Repository) -> Result<DiffStats, GitError>;
+
+    /// Formats the unified diff of the commit against its first parent,
+    /// like `git show --format= --first-parent`.
+    ///
+    /// Changes to binary files are printed as `Binary files a/... and
+    /// b/... differ`, instead of their content.
+    fn patch(&self, repo: &Repository) -> Result<String, GitError>;
+
+    /// Same as [`likely_rewritten_within()`](CommitExt::likely_rewritten_within)
+    /// with a threshold of one hour.
+    fn likely_rewritten(&self) -> bool {
+        self.likely_rewritten_within(60 * 60)
+    }
+
+    fn walk_changes<T, F>(
+        &self,
+        repo: &Repository,
+        format: DiffFormat,
+        mut f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diffs(repo, |diff| diff.walk_changes(format, &mut f))
+    }
+
+    /// Walks the changes of [`walk_diffs_with()`](CommitExt::walk_diffs_with).
+    fn walk_changes_with<T, F>(
+        &self,
+        repo: &Repository,
+        opts: &mut DiffOptions,
+        format: DiffFormat,
+        mut f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diffs_with(repo, opts, |diff| diff.walk_changes(format, &mut f))
+    }
+
+    /// Walks the changes of [`walk_diffs_with_find_options()`](CommitExt::walk_diffs_with_find_options).
+    fn walk_changes_with_find_options<T, F>(
+        &self,
+        repo: &Repository,
+        opts: &mut DiffOptions,
+        find_opts: &mut DiffFindOptions,
+        format: DiffFormat,
+        mut f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diffs_with_find_options(repo, opts, find_opts, |diff| {
+            diff.walk_changes(format, &mut f)
+        })
+    }
+
+    /// Same as [`walk_changes()`](CommitExt::walk_changes), but only
+    /// walks the changes to files under the directory `prefix`.
+    ///
+    /// Unlike [`DiffOptions::pathspec()`], renames are filtered after
+    /// they are detected, such that a file renamed into or out of `prefix`
+    /// is still reported as a single rename.
+    fn walk_changes_in<P, T, F>(
+        &self,
+        repo: &Repository,
+        prefix: P,
+        format: DiffFormat,
+        mut f: F,
+    ) -> Result<(), GitError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        let prefix = prefix.as_ref();
+        let is_under = |path: Option<&Path>| path.is_some_and(|path| path.starts_with(prefix));
+        self.walk_changes(repo, format, |delta, hunk, line| {
+            if is_under(delta.old_file().path()) || is_under(delta.new_file().path()) {
+                f(delta, hunk, line).finished()
+            } else {
+                Ok(false)
+            }
+        })
+    }
+
+    /// Same as [`walk_changes()`](CommitExt::walk_changes), but skips
+    /// the changes to files matching any of the pathspec `globs`, e.g.
+    /// `"*.lock"` or `"dist/**"`, such as generated or vendored files.
+    ///
+    /// The globs are compiled once, and matched against the new path
+    /// of each file, which is the old path for deleted files.
+    fn walk_changes_excluding<T, F>(
+        &self,
+        repo: &Repository,
+        globs: &[&str],
+        format: DiffFormat,
+        mut f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        let pathspec = Pathspec::new(globs.iter().copied())?;
+        self.walk_changes(repo, format, |delta, hunk, line| {
+            let path = delta.new_file().path().or_else(|| delta.old_file().path());
+            // An empty pathspec matches everything
+            let excluded = !globs.is_empty()
+                && path.is_some_and(|path| pathspec.matches_path(path, PathspecFlags::DEFAULT));
+            if excluded {
+                Ok(false)
+            } else {
+                f(delta, hunk, line).finished()
+            }
+        })
+    }
+
+    /// Walks the changes of [`walk_diff_first_parent()`](CommitExt::walk_diff_first_parent).
+    fn walk_changes_first_parent<T, F>(
+        &self,
+        repo: &Repository,
+        format: DiffFormat,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
         T: WalkOutput,
     {
-        for commit in self.commits()? {
-            if f(commit?).finished()? {
-                break;
-            }
-        }
-        Ok(())
+        self.walk_diff_first_parent(repo, |diff| diff.walk_changes(format, f))
     }
-}
 
-impl RepositoryExt for Repository {
-    fn commits(&self) -> Result<Commits<'_>, GitError> {
-        Commits::new(self)
+    /// Walks the changes of [`walk_diff_to()`](CommitExt::walk_diff_to).
+    fn walk_changes_to<T, F>(
+        &self,
+        repo: &Repository,
+        other: &Commit<'_>,
+        format: DiffFormat,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diff_to(repo, other, |diff| diff.walk_changes(format, f))
     }
 
-    fn count_commits(&self) -> Result<usize, GitError> {
-        Ok(revwalk(self)?.count())
+    /// Walks the changes of [`walk_diff_against()`](CommitExt::walk_diff_against).
+    fn walk_changes_against<T, F>(
+        &self,
+        repo: &Repository,
+        parent_index: usize,
+        format: DiffFormat,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diff_against(repo, parent_index, |diff| diff.walk_changes(format, f))
     }
-}
 
-pub struct Commits<'a> {
-    repo: &'a Repository,
-    revwalk: Revwalk<'a>,
-}
+    /// Walks the changes of [`walk_diff_from_empty()`](CommitExt::walk_diff_from_empty).
+    fn walk_changes_from_empty<T, F>(
+        &self,
+        repo: &Repository,
+        format: DiffFormat,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        T: WalkOutput,
+    {
+        self.walk_diff_from_empty(repo, |diff| diff.walk_changes(format, f))
+    }
 
-impl<'a> Commits<'a> {
-    fn new(repo: &'a Repository) -> Result<Self, GitError> {
-        let revwalk = revwalk(repo)?;
-        Ok(Self { repo, revwalk })
+    /// Returns the paths changed by the commit, like `git diff --name-only`.
+    ///
+    /// Only the deltas are inspected, so no patch content or blobs are
+    /// loaded. Renamed files are reported by their new path. For merge
+    /// commits, the paths changed relative to each parent are included,
+    /// each path only once.
+    fn changed_paths(&self, repo: &Repository) -> Result<Vec<PathBuf>, GitError> {
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        self.walk_diffs(repo, |diff| {
+            for delta in diff.deltas() {
+                let path = delta.new_file().path().or_else(|| delta.old_file().path());
+                if let Some(path) = path {
+                    if seen.insert(path.to_path_buf()) {
+                        paths.push(path.to_path_buf());
+                    }
+                }
+            }
+        })?;
+        Ok(paths)
     }
-}
 
-impl<'a> Iterator for Commits<'a> {
-    type Item = Result<git2::Commit<'a>, GitError>;
+    /// Returns the paths a merge commit changed relative to every one
+    /// of its parents, i.e. the files of git's combined diff, like
+    /// `git show --name-only --format= <merge>`.
+    ///
+    /// These are the files where the merge did not just take the
+    /// content of one side, e.g. conflict resolutions. Returns an empty
+    /// `Vec` for commits that are not merges.
+    fn merge_resolution_paths(&self, repo: &Repository) -> Result<Vec<PathBuf>, GitError> {
+        let mut per_parent = Vec::new();
+        self.walk_diffs(repo, |diff| {
+            let paths = diff
+                .deltas()
+                .filter_map(|delta| {
+                    let path = delta.new_file().path().or_else(|| delta.old_file().path());
+                    path.map(Path::to_path_buf)
+                })
+                .collect::<Vec<_>>();
+            per_parent.push(paths);
+        })?;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let oid = match self.revwalk.next()? {
-            Ok(oid) => oid,
-            Err(err) => return Some(Err(err)),
-        };
-        let commit = match self.repo.find_commit(oid) {
-            Ok(commit) => commit,
-            Err(err) => return Some(Err(err)),
+        let Some((first, others)) = per_parent.split_first() else {
+            return Ok(Vec::new());
         };
-        Some(Ok(commit))
+        let others = others
+            .iter()
+            .map(|paths| paths.iter().collect::<HashSet<_>>())
+            .collect::<Vec<_>>();
+        if others.is_empty() {
+            return Ok(Vec::new());
+        }
+        let paths = first
+            .iter()
+            .filter(|path| others.iter().all(|other| other.contains(path)))
+            .cloned()
+            .collect();
+        Ok(paths)
     }
 }
 
-fn revwalk(repo: &Repository) -> Result<Revwalk<'_>, GitError> {
-    let mut revwalk = repo.revwalk()?;
-    revwalk.set_sorting(Sort::REVERSE | Sort::TIME)?;
-    revwalk.push_head()?;
-    Ok(revwalk)
-}
-
-pub trait CommitExt {
+impl CommitExt for Commit<'_> {
     fn walk_diffs<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
     where
         F: FnMut(Diff<'_>) -> T,
-        T: WalkOutput;
+        T: WalkOutput,
+    {
+        self.walk_diffs_with(repo, &mut diff_options(), f)
+    }
 
-    fn walk_changes<T, F>(
+    fn walk_diffs_with<T, F>(
         &self,
         repo: &Repository,
-        format: DiffFormat,
-        mut f: F,
+        opts: &mut DiffOptions,
+        f: F,
     ) -> Result<(), GitError>
     where
-        F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
+        F: FnMut(Diff<'_>) -> T,
         T: WalkOutput,
     {
-        self.walk_diffs(repo, |diff| diff.walk_changes(format, &mut f))
+        walk_parent_diffs(self, repo, opts, None, f)
     }
-}
 
-impl CommitExt for Commit<'_> {
-    fn walk_diffs<T, F>(&self, repo: &Repository, mut f: F) -> Result<(), GitError>
+    fn walk_diffs_with_find_options<T, F>(
+        &self,
+        repo: &Repository,
+        opts: &mut DiffOptions,
+        find_opts: &mut DiffFindOptions,
+        f: F,
+    ) -> Result<(), GitError>
     where
         F: FnMut(Diff<'_>) -> T,
         T: WalkOutput,
+    {
+        walk_parent_diffs(self, repo, opts, Some(find_opts), f)
+    }
+
+    fn walk_diff_first_parent<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
     {
         let new_tree = self.tree()?;
         if self.parent_count() == 0 {
-            walk_diff(repo, None, Some(&new_tree), f)?;
+            walk_diff(repo, None, Some(&new_tree), f)
         } else {
-            for parent in self.parents() {
-                let old_tree = parent.tree()?;
-                walk_diff(repo, Some(&old_tree), Some(&new_tree), &mut f)?;
+            let old_tree = self.parent(0)?.tree()?;
+            walk_diff(repo, Some(&old_tree), Some(&new_tree), f)
+        }
+    }
+
+    fn walk_diff_from_empty<T, F>(&self, repo: &Repository, f: F) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
+    {
+        walk_diff(repo, None, Some(&self.tree()?), f)
+    }
+
+    fn walk_diff_to<T, F>(
+        &self,
+        repo: &Repository,
+        other: &Commit<'_>,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
+    {
+        walk_diff(repo, Some(&self.tree()?), Some(&other.tree()?), f)
+    }
+
+    fn walk_diff_against<T, F>(
+        &self,
+        repo: &Repository,
+        parent_index: usize,
+        f: F,
+    ) -> Result<(), GitError>
+    where
+        F: FnOnce(Diff<'_>) -> T,
+        T: WalkOutput,
+    {
+        let old_tree = self.parent(parent_index)?.tree()?;
+        walk_diff(repo, Some(&old_tree), Some(&self.tree()?), f)
+    }
+
+    fn is_merge(&self) -> bool {
+        self.parent_count() > 1
+    }
+
+    fn blob_ids(&self) -> Result<Vec<Oid>, GitError> {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+        self.tree()?.walk(TreeWalkMode::PreOrder, |_, entry| {
+            if entry.kind() == Some(ObjectType::Blob) && seen.insert(entry.id()) {
+                ids.push(entry.id());
+            }
+            TreeWalkResult::Ok
+        })?;
+        Ok(ids)
+    }
+
+    fn parent_shas(&self) -> Vec<String> {
+        self.parent_ids().map(|id| id.to_string()).collect()
+    }
+
+    fn parent_short_shas(&self) -> Result<Vec<String>, GitError> {
+        self.parents().map(|parent| parent.abbrev_sha()).collect()
+    }
+
+    fn short_sha(&self, len: usize) -> String {
+        let mut sha = self.id().to_string();
+        sha.truncate(len);
+        sha
+    }
+
+    fn abbrev_sha(&self) -> Result<String, GitError> {
+        let id = self.as_object().short_id()?;
+        Ok(String::from_utf8_lossy(&id).into_owned())
+    }
+
+    fn signature_raw(&self, repo: &Repository) -> Result<Option<(Buf, Buf)>, GitError> {
+        match repo.extract_signature(&self.id(), None) {
+            Ok(signature) => Ok(Some(signature)),
+            Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn is_signed(&self, repo: &Repository) -> Result<bool, GitError> {
+        Ok(self.signature_raw(repo)?.is_some())
+    }
+
+    fn signing_key_id(&self, repo: &Repository) -> Result<Option<String>, GitError> {
+        let signature = self.signature_raw(repo)?;
+        Ok(signature.and_then(|(signature, _)| signing_key_id(&signature)))
+    }
+
+    fn body_paragraphs(&self) -> Vec<&str> {
+        let Some(body) = self.body() else {
+            return Vec::new();
+        };
+
+        let mut paragraphs = Vec::new();
+        let mut start = None;
+        let mut end = 0;
+        let mut offset = 0;
+        for line in body.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if line.trim().is_empty() {
+                if let Some(start) = start.take() {
+                    paragraphs.push(&body[start..end]);
+                }
+            } else {
+                start.get_or_insert(line_start);
+                end = line_start + line.trim_end().len();
             }
         }
-        Ok(())
+        if let Some(start) = start {
+            paragraphs.push(&body[start..end]);
+        }
+        paragraphs
+    }
+
+    fn trailers(&self) -> Result<Vec<(String, String)>, GitError> {
+        let trailers = git2::message_trailers_bytes(self.message_bytes().to_vec())?;
+        let trailers = trailers
+            .iter()
+            .map(|(key, value)| {
+                (
+                    String::from_utf8_lossy(key).into_owned(),
+                    String::from_utf8_lossy(value).into_owned(),
+                )
+            })
+            .collect();
+        Ok(trailers)
+    }
+
+    fn format_fuller(&self) -> Result<String, GitError> {
+        let mut s = format!("commit {}\n", self.id());
+        if self.parent_count() > 1 {
+            s.push_str(&format!("Merge: {}\n", self.parent_short_shas()?.join(" ")));
+        }
+
+        let author = self.author();
+        let committer = self.committer();
+        s.push_str(&format!("Author:     {}\n", author));
+        s.push_str(&format!("AuthorDate: {}\n", format_git_date(author.when())));
+        s.push_str(&format!("Commit:     {}\n", committer));
+        s.push_str(&format!(
+            "CommitDate: {}\n",
+            format_git_date(committer.when())
+        ));
+        s.push('\n');
+
+        let message = String::from_utf8_lossy(self.message_bytes());
+        for line in message.trim_end().lines() {
+            s.push_str("    ");
+            s.push_str(line);
+            s.push('\n');
+        }
+
+        Ok(s)
+    }
+
+    fn format_stat(&self, repo: &Repository, width: usize) -> Result<String, GitError> {
+        let mut stat = String::new();
+        self.walk_diff_first_parent(repo, |diff| -> Result<(), GitError> {
+            stat = format_stat(repo, &diff, width)?;
+            Ok(())
+        })?;
+        Ok(stat)
+    }
+
+    fn stats(&self, repo: &Repository) -> Result<DiffStats, GitError> {
+        let old_tree = first_parent_tree(self)?;
+        diff_trees(repo, old_tree.as_ref(), Some(&self.tree()?))?.stats()
+    }
+
+    fn patch(&self, repo: &Repository) -> Result<String, GitError> {
+        let old_tree = first_parent_tree(self)?;
+        // Without `show_binary()`, such that binary content is omitted
+        let mut opts = DiffOptions::new();
+        let diff = diff_trees_with(
+            repo,
+            old_tree.as_ref(),
+            Some(&self.tree()?),
+            &mut opts,
+            None,
+        )?;
+
+        let mut patch = Vec::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if let origin @ ('+' | '-' | ' ') = line.origin() {
+                patch.push(origin as u8);
+            }
+            patch.extend_from_slice(line.content());
+            true
+        })?;
+        Ok(String::from_utf8_lossy(&patch).into_owned())
+    }
+
+    fn likely_rewritten_within(&self, secs: i64) -> bool {
+        let author = self.author();
+        let committer = self.committer();
+        if author.name_bytes() != committer.name_bytes()
+            || author.email_bytes() != committer.email_bytes()
+        {
+            return true;
+        }
+        committer.when().seconds() - author.when().seconds() > secs
+    }
+
+    fn to_info(&self) -> CommitInfo {
+        let lossy = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        let author = self.author();
+        let committer = self.committer();
+        CommitInfo {
+            sha: self.id().to_string(),
+            author_name: lossy(author.name_bytes()),
+            author_email: lossy(author.email_bytes()),
+            author_time: author.when().seconds(),
+            author_offset_minutes: author.when().offset_minutes(),
+            committer_name: lossy(committer.name_bytes()),
+            committer_email: lossy(committer.email_bytes()),
+            committer_time: committer.when().seconds(),
+            committer_offset_minutes: committer.when().offset_minutes(),
+            summary: self.summary_bytes().map(lossy),
+            body: self.body_bytes().map(lossy),
+        }
+    }
+}
+
+/// An owned snapshot of a commit's metadata, see [`CommitExt::to_info()`].
+///
+/// Names, emails, and messages that are not valid UTF-8 are
+/// converted lossily. Times are in seconds since the epoch, with
+/// the offset recorded in the commit.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_time: i64,
+    pub author_offset_minutes: i32,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_time: i64,
+    pub committer_offset_minutes: i32,
+    pub summary: Option<String>,
+    pub body: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CommitInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("CommitInfo", 11)?;
+        s.serialize_field("sha", &self.sha)?;
+        s.serialize_field("author_name", &self.author_name)?;
+        s.serialize_field("author_email", &self.author_email)?;
+        s.serialize_field("author_time", &self.author_time)?;
+        s.serialize_field("author_offset_minutes", &self.author_offset_minutes)?;
+        s.serialize_field("committer_name", &self.committer_name)?;
+        s.serialize_field("committer_email", &self.committer_email)?;
+        s.serialize_field("committer_time", &self.committer_time)?;
+        s.serialize_field("committer_offset_minutes", &self.committer_offset_minutes)?;
+        s.serialize_field("summary", &self.summary)?;
+        s.serialize_field("body", &self.body)?;
+        s.end()
+    }
+}
+
+fn first_parent_tree<'repo>(commit: &Commit<'repo>) -> Result<Option<Tree<'repo>>, GitError> {
+    match commit.parent_count() {
+        0 => Ok(None),
+        _ => Ok(Some(commit.parent(0)?.tree()?)),
     }
 }
 
@@ -159,15 +2067,114 @@ where
     F: FnOnce(Diff<'_>) -> T,
     T: WalkOutput,
 {
+    walk_diff_with(repo, old_tree, new_tree, &mut diff_options(), None, f)
+}
+
+fn walk_diff_with<T, F>(
+    repo: &Repository,
+    old_tree: Option<&Tree<'_>>,
+    new_tree: Option<&Tree<'_>>,
+    opts: &mut DiffOptions,
+    find_opts: Option<&mut DiffFindOptions>,
+    f: F,
+) -> Result<(), GitError>
+where
+    F: FnOnce(Diff<'_>) -> T,
+    T: WalkOutput,
+{
+    let diff = diff_trees_with(repo, old_tree, new_tree, opts, find_opts)?;
+    f(diff).finished()?;
+    Ok(())
+}
+
+/// Diffs `commit` against each of its parents in order, or against
+/// the empty tree if it has no parents.
+fn walk_parent_diffs<T, F>(
+    commit: &Commit<'_>,
+    repo: &Repository,
+    opts: &mut DiffOptions,
+    mut find_opts: Option<&mut DiffFindOptions>,
+    mut f: F,
+) -> Result<(), GitError>
+where
+    F: FnMut(Diff<'_>) -> T,
+    T: WalkOutput,
+{
+    let new_tree = commit.tree()?;
+    if commit.parent_count() == 0 {
+        walk_diff_with(repo, None, Some(&new_tree), opts, find_opts, f)?;
+    } else {
+        for parent in commit.parents() {
+            let old_tree = parent.tree()?;
+            walk_diff_with(
+                repo,
+                Some(&old_tree),
+                Some(&new_tree),
+                opts,
+                find_opts.as_deref_mut(),
+                &mut f,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn diff_options() -> DiffOptions {
     let mut opts = DiffOptions::new();
     opts.show_binary(true);
+    opts
+}
 
-    let mut diff = repo.diff_tree_to_tree(old_tree, new_tree, Some(&mut opts))?;
-    diff.find_similar(None)?;
+fn diff_trees<'repo>(
+    repo: &'repo Repository,
+    old_tree: Option<&Tree<'_>>,
+    new_tree: Option<&Tree<'_>>,
+) -> Result<Diff<'repo>, GitError> {
+    diff_trees_with(repo, old_tree, new_tree, &mut diff_options(), None)
+}
 
-    f(diff).finished()?;
+fn diff_trees_with<'repo>(
+    repo: &'repo Repository,
+    old_tree: Option<&Tree<'_>>,
+    new_tree: Option<&Tree<'_>>,
+    opts: &mut DiffOptions,
+    find_opts: Option<&mut DiffFindOptions>,
+) -> Result<Diff<'repo>, GitError> {
+    let mut diff = repo.diff_tree_to_tree(old_tree, new_tree, Some(opts))?;
+    diff.find_similar(find_opts)?;
 
-    Ok(())
+    Ok(diff)
+}
+
+pub trait SignatureExt {
+    /// Formats the time of the signature in its own recorded offset,
+    /// not the local machine's, as `YYYY-MM-DD HH:MM:SS +HHMM`
+    /// (like `git log --date=iso`).
+    fn local_time_string(&self) -> String;
+
+    /// Returns `true` if the signature matches any of the `(name, email)`
+    /// pairs in `identities`, e.g. for checking authors against an
+    /// allowlist.
+    ///
+    /// Emails are compared ASCII case-insensitively. An empty name matches
+    /// any name, such that `("", "jane@example.com")` matches solely
+    /// on the email.
+    fn matches_any(&self, identities: &[(&str, &str)]) -> bool;
+}
+
+impl SignatureExt for Signature<'_> {
+    fn local_time_string(&self) -> String {
+        format_iso_date(self.when())
+    }
+
+    fn matches_any(&self, identities: &[(&str, &str)]) -> bool {
+        let name = String::from_utf8_lossy(self.name_bytes());
+        let email = String::from_utf8_lossy(self.email_bytes());
+        identities.iter().any(|&(allowed_name, allowed_email)| {
+            (allowed_name.is_empty() || allowed_name == name)
+                && allowed_email.eq_ignore_ascii_case(&email)
+        })
+    }
 }
 
 pub trait DiffExt {
@@ -207,3 +2214,216 @@ impl DiffExt for Diff<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use git2::build::TreeUpdateBuilder;
+    use git2::FileMode;
+
+    /// Creates an empty repository in a fresh temporary directory.
+    fn init_repo(name: &str) -> Repository {
+        let dir = std::env::temp_dir().join(format!("git-commits-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Repository::init(dir).unwrap()
+    }
+
+    /// Commits the first parent's tree, or the empty tree, with `files`
+    /// written, or removed if their content is `None`, and points `HEAD`
+    /// at the new commit.
+    fn commit<'a>(
+        repo: &'a Repository,
+        parents: &[&Commit<'a>],
+        files: &[(&str, Option<&str>)],
+        (name, time): (&str, i64),
+    ) -> Commit<'a> {
+        let base = match parents.first() {
+            Some(parent) => parent.tree().unwrap(),
+            None => repo
+                .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+                .unwrap(),
+        };
+        let mut update = TreeUpdateBuilder::new();
+        for &(path, content) in files {
+            match content {
+                Some(content) => {
+                    let blob = repo.blob(content.as_bytes()).unwrap();
+                    update.upsert(path, blob, FileMode::Blob);
+                }
+                None => {
+                    update.remove(path);
+                }
+            }
+        }
+        let tree = repo
+            .find_tree(update.create_updated(repo, &base).unwrap())
+            .unwrap();
+
+        let email = format!("{}@example.com", name.to_lowercase());
+        let sig = Signature::new(name, &email, &Time::new(time, 0)).unwrap();
+        let oid = repo
+            .commit(None, &sig, &sig, "commit", &tree, parents)
+            .unwrap();
+        repo.set_head_detached(oid).unwrap();
+        repo.find_commit(oid).unwrap()
+    }
+
+    fn traced<'a>(trace: &'a [(Commit<'_>, PathBuf)]) -> Vec<(Oid, &'a str)> {
+        trace
+            .iter()
+            .map(|(commit, path)| (commit.id(), path.to_str().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn trace_content_ignores_other_branches() {
+        let repo = init_repo("trace-branches");
+        let base = commit(
+            &repo,
+            &[],
+            &[
+                ("f.txt", Some("f\n")),
+                ("g.txt", Some("g\n")),
+                ("h.txt", Some("h\n")),
+            ],
+            ("A", 1000),
+        );
+        let side_edit_f = commit(&repo, &[&base], &[("f.txt", Some("f2\n"))], ("A", 2000));
+        let main_touch_g = commit(&repo, &[&base], &[("g.txt", Some("g2\n"))], ("A", 3000));
+        let side_touch_h = commit(
+            &repo,
+            &[&side_edit_f],
+            &[("h.txt", Some("h2\n"))],
+            ("A", 4000),
+        );
+        commit(
+            &repo,
+            &[&main_touch_g, &side_touch_h],
+            &[("f.txt", Some("f2\n")), ("h.txt", Some("h2\n"))],
+            ("A", 5000),
+        );
+
+        let trace = repo.trace_content("f.txt").unwrap();
+        assert_eq!(
+            traced(&trace),
+            [(base.id(), "f.txt"), (side_edit_f.id(), "f.txt")]
+        );
+    }
+
+    #[test]
+    fn trace_content_follows_similar_rename() {
+        let repo = init_repo("trace-similar");
+        let content = (1..=16).map(|i| format!("line {i}\n")).collect::<String>();
+        let edited = content.replace("line 8\n", "line eight\n");
+
+        let add = commit(
+            &repo,
+            &[],
+            &[("src/old/a.txt", Some(&content))],
+            ("A", 1000),
+        );
+        let rename = commit(
+            &repo,
+            &[&add],
+            &[("src/old/a.txt", None), ("src/new/a.txt", Some(&edited))],
+            ("A", 2000),
+        );
+        let edit = commit(
+            &repo,
+            &[&rename],
+            &[("src/new/a.txt", Some("x\n"))],
+            ("A", 3000),
+        );
+
+        let trace = repo.trace_content("src/old/a.txt").unwrap();
+        assert_eq!(
+            traced(&trace),
+            [
+                (add.id(), "src/old/a.txt"),
+                (rename.id(), "src/new/a.txt"),
+                (edit.id(), "src/new/a.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_content_follows_readded_blob() {
+        let repo = init_repo("trace-readd");
+        let add = commit(&repo, &[], &[("a.txt", Some("x\n"))], ("A", 1000));
+        let delete = commit(
+            &repo,
+            &[&add],
+            &[("a.txt", None), ("b.txt", Some("y\n"))],
+            ("A", 2000),
+        );
+        let readd = commit(&repo, &[&delete], &[("c.txt", Some("x\n"))], ("A", 3000));
+
+        let trace = repo.trace_content("a.txt").unwrap();
+        assert_eq!(traced(&trace), [(add.id(), "a.txt"), (readd.id(), "c.txt")]);
+    }
+
+    #[test]
+    fn ref_at_time_resolves_short_names() {
+        let repo = init_repo("ref-at-time");
+        let first = commit(&repo, &[], &[("a.txt", Some("a\n"))], ("A", 1000));
+        repo.reference("refs/heads/main", first.id(), true, "create")
+            .unwrap();
+
+        let later = Time::new(i64::from(u32::MAX), 0);
+        for name in ["main", "refs/heads/main"] {
+            let found = repo.ref_at_time(name, later).unwrap();
+            assert_eq!(found.map(|commit| commit.id()), Some(first.id()));
+            assert!(repo.ref_at_time(name, Time::new(0, 0)).unwrap().is_none());
+        }
+
+        for name in ["nope", "refs/heads/nope"] {
+            let err = repo.ref_at_time(name, later).unwrap_err();
+            assert_eq!(err.code(), ErrorCode::NotFound);
+        }
+    }
+
+    #[test]
+    fn range_composes_with_author_filter() {
+        let repo = init_repo("range-author");
+        let v1 = commit(&repo, &[], &[("a.txt", Some("1\n"))], ("Alice", 1000));
+        let a2 = commit(&repo, &[&v1], &[("a.txt", Some("2\n"))], ("Alice", 2000));
+        let b3 = commit(&repo, &[&a2], &[("a.txt", Some("3\n"))], ("Bob", 3000));
+        let v2 = commit(&repo, &[&b3], &[("a.txt", Some("4\n"))], ("Alice", 4000));
+        commit(&repo, &[&v2], &[("a.txt", Some("5\n"))], ("Bob", 5000));
+        repo.tag_lightweight("v1", v1.as_object(), false).unwrap();
+        repo.tag_lightweight("v2", v2.as_object(), false).unwrap();
+
+        let by_author = |pattern: &str| {
+            repo.commits_range("v1..v2", Sort::TIME)
+                .unwrap()
+                .filter_author(pattern)
+                .map(|commit| commit.unwrap().id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(by_author("alice"), [v2.id(), a2.id()]);
+        assert_eq!(by_author("bob@example.com"), [b3.id()]);
+    }
+
+    #[test]
+    fn collect_reversed_replays_oldest_first() {
+        let repo = init_repo("collect-reversed");
+        let first = commit(&repo, &[], &[("a.txt", Some("1\n"))], ("A", 1000));
+        let second = commit(&repo, &[&first], &[("a.txt", Some("2\n"))], ("A", 2000));
+        let third = commit(&repo, &[&second], &[("a.txt", Some("3\n"))], ("A", 3000));
+
+        let infos = repo
+            .commits_from("HEAD", Sort::TIME)
+            .unwrap()
+            .collect_reversed()
+            .unwrap();
+        let shas = infos
+            .iter()
+            .map(|info| info.sha.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shas,
+            [first.id(), second.id(), third.id()].map(|oid| oid.to_string())
+        );
+    }
+}
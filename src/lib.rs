@@ -5,16 +5,32 @@ pub mod prelude {
     pub use crate::ext::prelude::*;
 }
 
+mod date;
 mod ext;
+mod signing;
+mod stat;
 
 pub use git2::Error as GitError;
 
 pub use crate::prelude::*;
 
-use git2::{Commit, DiffDelta, DiffFormat, DiffHunk, DiffLine, Repository};
+use std::ffi::OsStr;
+use std::path::Path;
+
+use git2::{Commit, DiffDelta, DiffFormat, DiffHunk, DiffLine, Repository, RepositoryOpenFlags};
 
 use crate::ext::WalkOutput;
 
+/// Opens the repository at exactly `path`, without searching parent
+/// directories, i.e. fails if `path` is not the root of a repository.
+#[inline]
+pub fn open_no_search<P>(path: P) -> Result<Repository, GitError>
+where
+    P: AsRef<Path>,
+{
+    Repository::open_ext(path, RepositoryOpenFlags::NO_SEARCH, None::<&OsStr>)
+}
+
 #[inline]
 pub fn commits(repo: &Repository) -> Result<Commits<'_>, GitError> {
     repo.commits()
@@ -45,5 +61,5 @@ where
     F: FnMut(DiffDelta<'_>, Option<DiffHunk<'_>>, DiffLine<'_>) -> T,
     T: WalkOutput,
 {
-    commit.walk_changes(&repo, format, f)
+    commit.walk_changes(repo, format, f)
 }
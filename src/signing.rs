@@ -0,0 +1,391 @@
+/// Returns the issuer key ID of an armored OpenPGP signature, or the
+/// `SHA256:` public key fingerprint of an armored SSH signature.
+///
+/// The signature is only parsed, not verified.
+pub(crate) fn signing_key_id(signature: &[u8]) -> Option<String> {
+    let signature = std::str::from_utf8(signature).ok()?;
+    if let Some(data) = dearmor(signature, "PGP SIGNATURE") {
+        pgp_issuer(&data)
+    } else if let Some(data) = dearmor(signature, "SSH SIGNATURE") {
+        ssh_fingerprint(&data)
+    } else {
+        None
+    }
+}
+
+fn dearmor(text: &str, label: &str) -> Option<Vec<u8>> {
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+
+    let mut lines = text.lines().map(str::trim);
+    lines.find(|&line| line == begin)?;
+
+    let mut body = String::new();
+    for line in lines {
+        if line == end {
+            return base64_decode(&body);
+        }
+        // Skip armor headers (`Key: Value`) and the OpenPGP checksum (`=XXXX`)
+        if line.contains(':') || line.starts_with('=') {
+            continue;
+        }
+        body.push_str(line);
+    }
+    None
+}
+
+/// See [RFC 4880 Section 5.2](https://www.rfc-editor.org/rfc/rfc4880#section-5.2).
+fn pgp_issuer(data: &[u8]) -> Option<String> {
+    let (tag, body) = pgp_packet(data)?;
+    // Signature packet
+    if tag != 2 {
+        return None;
+    }
+
+    let (hashed, unhashed) = match body.first()? {
+        3 => return body.get(7..15).map(hex),
+        4 => {
+            let (hashed, rest) = length_prefixed(body.get(4..)?, 2)?;
+            let (unhashed, _) = length_prefixed(rest, 2)?;
+            (hashed, unhashed)
+        }
+        5 | 6 => {
+            let (hashed, rest) = length_prefixed(body.get(4..)?, 4)?;
+            let (unhashed, _) = length_prefixed(rest, 4)?;
+            (hashed, unhashed)
+        }
+        _ => return None,
+    };
+
+    let mut key_id = None;
+    for (kind, data) in pgp_subpackets(hashed).chain(pgp_subpackets(unhashed)) {
+        match (kind, data.split_first()) {
+            // Issuer Key ID
+            (16, _) if data.len() == 8 => return Some(hex(data)),
+            // Issuer Fingerprint, v4 keys use the last 8 bytes as key ID
+            (33, Some((4, fpr))) if fpr.len() == 20 => key_id = Some(hex(&fpr[12..])),
+            // Issuer Fingerprint, v5 and v6 keys use the first 8 bytes as key ID
+            (33, Some((5 | 6, fpr))) if fpr.len() == 32 => key_id = Some(hex(&fpr[..8])),
+            _ => {}
+        }
+    }
+    key_id
+}
+
+fn pgp_packet(data: &[u8]) -> Option<(u8, &[u8])> {
+    let (&header, rest) = data.split_first()?;
+    if header & 0x80 == 0 {
+        return None;
+    }
+
+    let (tag, len, rest) = if header & 0x40 != 0 {
+        let (len, rest) = pgp_new_length(rest)?;
+        (header & 0x3F, len, rest)
+    } else {
+        let (len, rest) = match header & 0x03 {
+            0 => (usize::from(*rest.first()?), rest.get(1..)?),
+            1 => (be_uint(rest.get(..2)?), rest.get(2..)?),
+            2 => (be_uint(rest.get(..4)?), rest.get(4..)?),
+            _ => (rest.len(), rest),
+        };
+        ((header >> 2) & 0x0F, len, rest)
+    };
+    Some((tag, rest.get(..len)?))
+}
+
+/// Partial body lengths are not supported, as signatures
+/// are never that long.
+fn pgp_new_length(data: &[u8]) -> Option<(usize, &[u8])> {
+    let first = usize::from(*data.first()?);
+    match first {
+        0..=191 => Some((first, data.get(1..)?)),
+        192..=223 => {
+            let second = usize::from(*data.get(1)?);
+            Some((((first - 192) << 8) + second + 192, data.get(2..)?))
+        }
+        255 => Some((be_uint(data.get(1..5)?), data.get(5..)?)),
+        _ => None,
+    }
+}
+
+/// See [RFC 4880 Section 5.2.3.1](https://www.rfc-editor.org/rfc/rfc4880#section-5.2.3.1).
+/// Unlike packet lengths, every first octet from 192 to 254 starts
+/// a two-octet length, since subpackets have no partial lengths.
+fn pgp_subpacket_length(data: &[u8]) -> Option<(usize, &[u8])> {
+    let first = usize::from(*data.first()?);
+    match first {
+        0..=191 => Some((first, data.get(1..)?)),
+        192..=254 => {
+            let second = usize::from(*data.get(1)?);
+            Some((((first - 192) << 8) + second + 192, data.get(2..)?))
+        }
+        _ => Some((be_uint(data.get(1..5)?), data.get(5..)?)),
+    }
+}
+
+fn pgp_subpackets(mut data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    std::iter::from_fn(move || {
+        let (len, rest) = pgp_subpacket_length(data)?;
+        let subpacket = rest.get(..len)?;
+        data = &rest[len..];
+        let (&kind, body) = subpacket.split_first()?;
+        // Strip the critical bit
+        Some((kind & 0x7F, body))
+    })
+}
+
+/// See [`PROTOCOL.sshsig`](https://github.com/openssh/openssh-portable/blob/master/PROTOCOL.sshsig).
+fn ssh_fingerprint(data: &[u8]) -> Option<String> {
+    let rest = data.strip_prefix(b"SSHSIG")?;
+    // Skip the version
+    let (public_key, _) = length_prefixed(rest.get(4..)?, 4)?;
+    Some(format!("SHA256:{}", base64_encode(&sha256(public_key))))
+}
+
+/// Splits off a big-endian length prefix of `n` bytes and the data
+/// it describes.
+fn length_prefixed(data: &[u8], n: usize) -> Option<(&[u8], &[u8])> {
+    let len = be_uint(data.get(..n)?);
+    let data = data.get(n..)?;
+    Some((data.get(..len)?, &data[len..]))
+}
+
+fn be_uint(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |acc, &byte| (acc << 8) | usize::from(byte))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().take_while(|&c| c != b'=') {
+        let value = BASE64.iter().position(|&b| b == c)?;
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Encodes without padding, as used by SSH fingerprints.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let acc = chunk.iter().enumerate().fold(0u32, |acc, (i, &byte)| {
+            acc | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            text.push(BASE64[(acc >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+    text
+}
+
+/// See [FIPS 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, h) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PGP_SIGNATURE: &str = concat!(
+        "-----BEGIN PGP SIGNATURE-----\n",
+        "\n",
+        "iQG8BAABCgAmFiEEfLXOFXUWEAGBT4vmwnqMwNalk2kFAmrPugMIHHRAeC5jb20A\n",
+        "CgkQwnqMwNalk2nxHQv5Ad9eiOyqrej383i9czuk1Ap1fy7tdU+7tnFFFSl9IPsW\n",
+        "SsZpLoGuW0+xeFO9S06NxdkBCIRCxtUGA3fEwTXpJnSsDKSmrnh6hp/xd91s+eye\n",
+        "HRPavC/yGoHvKINlG3q9FawnmDvWasiUai8C7aP2Waf39d6Dslg8mV1DGq2N1CiO\n",
+        "jJyarTPWJurmEZsGGJsEoZCE8WFmMVwzzCfYshgOsio2NCGNT7KhArerO4AADRH0\n",
+        "dHiKPPeWOIKgp8L7MgRRfSrMR8bCoA/jP1QZX2rKXOduHADPSF+GyuPcggRgRTkK\n",
+        "D49y5j/7FpvB/zt+6xEcwP1lGfnx4w9XZbdVQugznBygg8lUwRRsEUHQ/NzfBqSk\n",
+        "ThTg8q0CwxPXE4FszHHzcuJRFCZJ4pDQSFqpbHXISnbZPeJfHbU96CRtYxwM2V3W\n",
+        "QhPAxUi7+tAq8K9v/G2Bg78IJ7jlu6/yCK9xtkDYttOHbQCXl0meoj5VmKVYOC0x\n",
+        "jNQV66t2wkI+dIoRSUkk\n",
+        "=TkW9\n",
+        "-----END PGP SIGNATURE-----\n",
+    );
+
+    const SSH_SIGNATURE: &str = concat!(
+        "-----BEGIN SSH SIGNATURE-----\n",
+        "U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgU+813a24ukZ4ted7OdfnswxeM2\n",
+        "r/YJjPyidigSUC1OcAAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5\n",
+        "AAAAQBIdZpUmqFn6ogevuKsssefYe03U9xIJDiWAtSjTzRa+7sDJ0UU1EoCBGVR+bmLLdX\n",
+        "MWVhrQU7KyqKfmOrvIUQY=\n",
+        "-----END SSH SIGNATURE-----\n",
+    );
+
+    #[test]
+    fn pgp_key_id() {
+        let key_id = signing_key_id(PGP_SIGNATURE.as_bytes());
+        assert_eq!(key_id.as_deref(), Some("C27A8CC0D6A59369"));
+    }
+
+    #[test]
+    fn ssh_key_id() {
+        let key_id = signing_key_id(SSH_SIGNATURE.as_bytes());
+        assert_eq!(
+            key_id.as_deref(),
+            Some("SHA256:9VnOu3eWWErwneKvIMoLUl0Ra/OlLwicr4XBmJLrkGY")
+        );
+    }
+
+    #[test]
+    fn unknown_signature() {
+        assert_eq!(signing_key_id(b"not a signature"), None);
+    }
+
+    /// An issuer subpacket after a subpacket whose length starts
+    /// with an octet in `224..=254`.
+    #[test]
+    fn pgp_issuer_after_long_subpacket() {
+        let notation_len = 9000;
+        let first = (notation_len - 192) / 256 + 192;
+        assert!((224..=254).contains(&first));
+
+        let mut hashed = vec![first as u8, ((notation_len - 192) % 256) as u8, 20];
+        hashed.resize(hashed.len() + notation_len - 1, 0);
+        hashed.extend_from_slice(&[9, 16, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Version, signature type, public key and hash algorithms
+        let mut body = vec![4, 0, 1, 10];
+        body.extend_from_slice(&(hashed.len() as u16).to_be_bytes());
+        body.extend_from_slice(&hashed);
+        body.extend_from_slice(&[0, 0]);
+
+        let mut packet = vec![0xC2, 255];
+        packet.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        packet.extend_from_slice(&body);
+
+        assert_eq!(pgp_issuer(&packet).as_deref(), Some("0102030405060708"));
+    }
+
+    #[test]
+    fn sha256_known_answers() {
+        let cases: [(&[u8], &str); 3] = [
+            (
+                b"",
+                "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855",
+            ),
+            (
+                b"abc",
+                "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248D6A61D20638B8E5C026930C3E6039A33CE45964FF2167F6ECEDD419DB06C1",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(hex(&sha256(input)), expected);
+        }
+    }
+
+    #[test]
+    fn base64_known_answers() {
+        let cases = [
+            ("", ""),
+            ("f", "Zg"),
+            ("fo", "Zm8"),
+            ("foo", "Zm9v"),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, text) in cases {
+            assert_eq!(base64_encode(bytes.as_bytes()), text);
+            assert_eq!(base64_decode(text).as_deref(), Some(bytes.as_bytes()));
+        }
+        assert_eq!(base64_decode("Zm8=").as_deref(), Some(&b"fo"[..]));
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        for len in 0..bytes.len() {
+            let text = base64_encode(&bytes[..len]);
+            assert_eq!(base64_decode(&text).as_deref(), Some(&bytes[..len]));
+        }
+    }
+}
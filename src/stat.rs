@@ -0,0 +1,355 @@
+use git2::{Delta, Diff, Patch, Repository};
+
+use crate::ext::RepositoryExt;
+use crate::GitError;
+
+struct FileStat {
+    name: String,
+    added: usize,
+    deleted: usize,
+    binary: bool,
+}
+
+/// Formats `diff` like `git diff --stat=<width>`, following the
+/// layout and scaling of `show_stats()` in git's `diff.c`.
+pub(crate) fn format_stat(
+    repo: &Repository,
+    diff: &Diff<'_>,
+    width: usize,
+) -> Result<String, GitError> {
+    let files = file_stats(repo, diff)?;
+    if files.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut max_len = 0;
+    let mut max_change = 0;
+    let mut bin_width = 0;
+    let mut number_width = 0;
+    for file in &files {
+        max_len = max_len.max(file.name.len());
+        if file.binary {
+            // "Bin XXX -> YYY bytes"
+            let w = 14 + decimal_width(file.added) + decimal_width(file.deleted);
+            bin_width = bin_width.max(w);
+            // Display change counts aligned with "Bin"
+            number_width = 3;
+            continue;
+        }
+        max_change = max_change.max(file.added + file.deleted);
+    }
+    number_width = number_width.max(decimal_width(max_change));
+
+    // Guarantee 3/8*16==6 for the graph part
+    // and 5/8*16==10 for the filename part
+    let width = width.max(16 + 6 + number_width);
+
+    let mut graph_width = if max_change + 4 > bin_width {
+        max_change
+    } else {
+        bin_width - 4
+    };
+    let mut name_width = max_len;
+
+    if name_width + number_width + 6 + graph_width > width {
+        let max_graph_width = (width * 3 / 8).saturating_sub(number_width + 6);
+        if graph_width > max_graph_width {
+            graph_width = max_graph_width.max(6);
+        }
+        let available = width.saturating_sub(number_width + 6 + graph_width);
+        if name_width > available {
+            name_width = available;
+        } else {
+            graph_width = width - number_width - 6 - name_width;
+        }
+    }
+
+    let mut out = String::new();
+    let mut adds = 0;
+    let mut dels = 0;
+    for file in &files {
+        // Truncate the start of the filename, preferably at a slash
+        let mut prefix = "";
+        let mut name = file.name.as_str();
+        let mut len = name_width;
+        if name_width < name.len() {
+            prefix = "...";
+            len = len.saturating_sub(3);
+            name = &name[name.len() - len..];
+            if let Some(slash) = name.find('/') {
+                name = &name[slash..];
+            }
+        }
+        let padding = len.saturating_sub(name.len());
+
+        out.push_str(&format!(
+            " {prefix}{name}{:padding$} | ",
+            "",
+            padding = padding
+        ));
+
+        if file.binary {
+            out.push_str(&format!("{:>number_width$}", "Bin"));
+            if file.added != 0 || file.deleted != 0 {
+                out.push_str(&format!(" {} -> {} bytes", file.deleted, file.added));
+            }
+            out.push('\n');
+            continue;
+        }
+
+        adds += file.added;
+        dels += file.deleted;
+
+        let (mut add, mut del) = (file.added, file.deleted);
+        if graph_width <= max_change {
+            let mut total = scale_linear(add + del, graph_width, max_change);
+            if total < 2 && add != 0 && del != 0 {
+                total = 2;
+            }
+            if add < del {
+                add = scale_linear(add, graph_width, max_change);
+                del = total - add;
+            } else {
+                del = scale_linear(del, graph_width, max_change);
+                add = total - del;
+            }
+        }
+
+        let change = file.added + file.deleted;
+        out.push_str(&format!("{change:>number_width$}"));
+        if change != 0 {
+            out.push(' ');
+        }
+        out.push_str(&"+".repeat(add));
+        out.push_str(&"-".repeat(del));
+        out.push('\n');
+    }
+
+    out.push_str(&summary(files.len(), adds, dels));
+    Ok(out)
+}
+
+fn file_stats(repo: &Repository, diff: &Diff<'_>) -> Result<Vec<FileStat>, GitError> {
+    let mut files = Vec::with_capacity(diff.deltas().len());
+    for (i, delta) in diff.deltas().enumerate() {
+        let old_path = delta.old_file().path_bytes().unwrap_or_default();
+        let new_path = delta.new_file().path_bytes().unwrap_or_default();
+        let name = match delta.status() {
+            Delta::Renamed | Delta::Copied => pprint_rename(old_path, new_path),
+            _ => quote_path(new_path),
+        };
+
+        let patch = Patch::from_diff(diff, i)?;
+        let binary = match &patch {
+            Some(patch) => patch.delta().flags().is_binary(),
+            None => delta.flags().is_binary(),
+        };
+
+        let (added, deleted) = if binary {
+            let size = |id: git2::Oid| match id.is_zero() {
+                true => Ok(0),
+                false => repo.blob_size(id).map(|size| size as usize),
+            };
+            (size(delta.new_file().id())?, size(delta.old_file().id())?)
+        } else {
+            match patch {
+                Some(patch) => {
+                    let (_, added, deleted) = patch.line_stats()?;
+                    (added, deleted)
+                }
+                None => (0, 0),
+            }
+        };
+
+        files.push(FileStat {
+            name,
+            added,
+            deleted,
+            binary,
+        });
+    }
+    Ok(files)
+}
+
+fn summary(files: usize, insertions: usize, deletions: usize) -> String {
+    let plural = |n: usize, one: &str, many: &str| {
+        if n == 1 {
+            format!("{n} {one}")
+        } else {
+            format!("{n} {many}")
+        }
+    };
+
+    let mut s = format!(" {}", plural(files, "file changed", "files changed"));
+    if insertions != 0 || deletions == 0 {
+        s.push_str(", ");
+        s.push_str(&plural(insertions, "insertion(+)", "insertions(+)"));
+    }
+    if deletions != 0 || insertions == 0 {
+        s.push_str(", ");
+        s.push_str(&plural(deletions, "deletion(-)", "deletions(-)"));
+    }
+    s.push('\n');
+    s
+}
+
+/// Scales linearly as if the allotted width is one column shorter
+/// than it is, and then adds 1, such that at least one `+` or `-`
+/// is shown for any change.
+fn scale_linear(it: usize, width: usize, max_change: usize) -> usize {
+    if it == 0 {
+        return 0;
+    }
+    1 + it * (width - 1) / max_change
+}
+
+fn decimal_width(n: usize) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Formats a rename as `a => b`, collapsing the common leading and
+/// trailing directories, e.g. `src/{old => new}/lib.rs`.
+fn pprint_rename(a: &[u8], b: &[u8]) -> String {
+    if needs_quoting(a) || needs_quoting(b) {
+        return format!("{} => {}", quote_path(a), quote_path(b));
+    }
+
+    // Common prefix, up to and including the last slash
+    let mut pfx_len = 0;
+    for (i, (x, y)) in a.iter().zip(b).enumerate() {
+        if x != y {
+            break;
+        }
+        if *x == b'/' {
+            pfx_len = i + 1;
+        }
+    }
+
+    // Common suffix, starting at the first slash. If there is a common
+    // prefix, it ends in a slash, which the suffix may overlap with.
+    let min_start = pfx_len.saturating_sub(1);
+    let mut sfx_len = 0;
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > min_start && j > min_start && a[i - 1] == b[j - 1] {
+        i -= 1;
+        j -= 1;
+        if a[i] == b'/' {
+            sfx_len = a.len() - i;
+        }
+    }
+
+    let a_mid = &a[pfx_len..a.len().saturating_sub(sfx_len).max(pfx_len)];
+    let b_mid = &b[pfx_len..b.len().saturating_sub(sfx_len).max(pfx_len)];
+
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    if pfx_len + sfx_len == 0 {
+        format!("{} => {}", text(a_mid), text(b_mid))
+    } else {
+        format!(
+            "{}{{{} => {}}}{}",
+            text(&a[..pfx_len]),
+            text(a_mid),
+            text(b_mid),
+            text(&a[a.len() - sfx_len..]),
+        )
+    }
+}
+
+fn needs_quoting(path: &[u8]) -> bool {
+    path.iter()
+        .any(|&b| b < 0x20 || b == b'"' || b == b'\\' || b >= 0x7F)
+}
+
+/// Quotes paths like git does by default (`core.quotePath`), e.g.
+/// `"\303\244.txt"` for `ä.txt`.
+fn quote_path(path: &[u8]) -> String {
+    if !needs_quoting(path) {
+        return String::from_utf8_lossy(path).into_owned();
+    }
+
+    let mut s = String::from("\"");
+    for &b in path {
+        match b {
+            0x07 => s.push_str("\\a"),
+            0x08 => s.push_str("\\b"),
+            b'\t' => s.push_str("\\t"),
+            b'\n' => s.push_str("\\n"),
+            0x0B => s.push_str("\\v"),
+            0x0C => s.push_str("\\f"),
+            b'\r' => s.push_str("\\r"),
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            b if !(0x20..0x7F).contains(&b) => s.push_str(&format!("\\{b:03o}")),
+            b => s.push(b as char),
+        }
+    }
+    s.push('"');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_collapses_common_directories() {
+        let cases = [
+            ("d1/d2/z.txt", "d1/z.txt", "d1/{d2 => }/z.txt"),
+            ("p/q/r.txt", "p/qq/r.txt", "p/{q => qq}/r.txt"),
+            ("x/a.txt", "a.txt", "x/a.txt => a.txt"),
+            ("a.txt", "b.txt", "a.txt => b.txt"),
+            ("src/old.rs", "src/new.rs", "src/{old.rs => new.rs}"),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(pprint_rename(a.as_bytes(), b.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn rename_with_quoted_paths() {
+        assert_eq!(
+            pprint_rename(b"a.txt", "\u{e4}.txt".as_bytes()),
+            r#"a.txt => "\303\244.txt""#
+        );
+    }
+
+    #[test]
+    fn scale_linear_shows_at_least_one_column() {
+        assert_eq!(scale_linear(0, 10, 100), 0);
+        assert_eq!(scale_linear(1, 10, 100), 1);
+        assert_eq!(scale_linear(50, 10, 100), 5);
+        assert_eq!(scale_linear(100, 10, 100), 10);
+    }
+
+    #[test]
+    fn quote_path_like_core_quote_path() {
+        assert_eq!(quote_path(b"src/lib.rs"), "src/lib.rs");
+        assert_eq!(quote_path("\u{e4}.txt".as_bytes()), r#""\303\244.txt""#);
+        assert_eq!(quote_path(b"a\tb"), r#""a\tb""#);
+        assert_eq!(quote_path(b"a\"b"), r#""a\"b""#);
+        assert_eq!(quote_path(b"a\\b"), r#""a\\b""#);
+        assert_eq!(quote_path(b"a\x01b"), r#""a\001b""#);
+    }
+
+    #[test]
+    fn summary_pluralization() {
+        assert_eq!(summary(1, 1, 0), " 1 file changed, 1 insertion(+)\n");
+        assert_eq!(summary(2, 0, 3), " 2 files changed, 3 deletions(-)\n");
+        assert_eq!(
+            summary(3, 2, 1),
+            " 3 files changed, 2 insertions(+), 1 deletion(-)\n"
+        );
+        assert_eq!(
+            summary(1, 0, 0),
+            " 1 file changed, 0 insertions(+), 0 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn decimal_width_counts_digits() {
+        assert_eq!(decimal_width(0), 1);
+        assert_eq!(decimal_width(9), 1);
+        assert_eq!(decimal_width(10), 2);
+        assert_eq!(decimal_width(12345), 5);
+    }
+}